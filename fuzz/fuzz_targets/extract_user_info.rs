@@ -0,0 +1,30 @@
+#![no_main]
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{HeaderValue, Request},
+};
+use libfuzzer_sys::fuzz_target;
+use user_info_middleware::ExtractUserInfo;
+
+// Feed arbitrary bytes as the `X-Endpoint-API-UserInfo` header value through the full decode/parse pipeline.
+// The extraction must only ever return `Ok` or a rejection, never panic.
+fuzz_target!(|data: &[u8]| {
+    let Ok(user_info) = HeaderValue::from_bytes(data) else {
+        return;
+    };
+
+    let mut parts = Request::builder()
+        .header("X-Endpoint-API-UserInfo", user_info)
+        .body(Body::empty())
+        .unwrap()
+        .into_parts()
+        .0;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let _ = runtime.block_on(ExtractUserInfo::from_request_parts(&mut parts, &()));
+});
@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, RwLock},
+};
+
+/// This is the set of expected audiences, shared between the configuration and the application so it can be swapped at runtime.
+/// It's useful during audience migrations, to accept both the old and the new audiences without redeploying.
+///
+/// Each extraction loads a snapshot of the set, via a read lock held only for the time to clone an `Arc`.
+/// A swap replaces the whole set at once, so an extraction sees either the previous set or the new one, never a mix of both.
+///
+/// # Examples
+///
+/// ```rust
+/// use user_info_middleware::{SharedAudiences, UserInfoConfig};
+///
+/// let audiences = SharedAudiences::new(["my-old-audience", "my-new-audience"]);
+/// let config = UserInfoConfig::new().expected_audiences(audiences.clone());
+///
+/// // Later, once the migration is over.
+/// audiences.store(["my-new-audience"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedAudiences(Arc<RwLock<Arc<BTreeSet<String>>>>);
+
+impl SharedAudiences {
+    /// Create a new shared set of expected audiences.
+    pub fn new<I, A>(audiences: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<String>,
+    {
+        Self(Arc::new(RwLock::new(Arc::new(
+            audiences.into_iter().map(Into::into).collect(),
+        ))))
+    }
+
+    /// Get a snapshot of the current expected audiences.
+    pub fn load(&self) -> Arc<BTreeSet<String>> {
+        match self.0.read() {
+            Ok(audiences) => audiences.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Swap the expected audiences, for all the following extractions.
+    pub fn store<I, A>(&self, audiences: I)
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<String>,
+    {
+        let audiences = Arc::new(audiences.into_iter().map(Into::into).collect());
+
+        match self.0.write() {
+            Ok(mut current) => *current = audiences,
+            Err(poisoned) => *poisoned.into_inner() = audiences,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SharedAudiences;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_audience_shared_audiences_store() {
+        let audiences = SharedAudiences::new(["my-audience"]);
+        let snapshot = audiences.load();
+
+        audiences.clone().store(["my-new-audience"]);
+
+        assert_eq!(*snapshot, BTreeSet::from(["my-audience".to_string()]));
+        assert_eq!(
+            *audiences.load(),
+            BTreeSet::from(["my-new-audience".to_string()])
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    ExtractUserInfoRejection, UserInfoConfig, UserInfoRejection, extract::extract_decoded,
+    logging::log_error,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::{Map, Number, Value};
+
+/// This is the maximum nesting of the CBOR arrays, maps and tags, bounding the recursion on a hostile payload.
+const MAX_DEPTH: usize = 128;
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, as a base 64 encoded CBOR (RFC 8949) value, as sent by binary-oriented gateways.
+/// The CBOR value is converted into a JSON value, then goes through the same pipeline as `ExtractUserInfo`, i.e. the same configuration, validations and rejections.
+/// The map keys must be text strings, the byte strings are converted into arrays of numbers, the tags are ignored and the undefined value is `null`.
+/// If the decoded `X-Endpoint-API-UserInfo` header isn't a valid CBOR, or has no JSON counterpart, e.g. a NaN, it returns a 400 Bad Request error with a specific message.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractCborUserInfo;
+///
+/// async fn handler(ExtractCborUserInfo(user_info): ExtractCborUserInfo) {
+///     println!("User Info: {:?}", user_info);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractCborUserInfo(pub Value);
+
+impl<S> FromRequestParts<S> for ExtractCborUserInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract_decoded(parts, "cbor", parse_cbor).await?;
+
+        Ok(ExtractCborUserInfo(extraction.value))
+    }
+}
+
+/// Parse the decoded bytes of a header value as CBOR, returning its JSON counterpart.
+fn parse_cbor(
+    decoded: &[u8],
+    header: &str,
+    config: &UserInfoConfig,
+) -> Result<Value, UserInfoRejection> {
+    let mut reader = Reader {
+        bytes: decoded,
+        position: 0,
+    };
+
+    let parsed = reader.value(0).and_then(|parsed| match reader.position {
+        position if position == decoded.len() => Ok(parsed),
+        _ => Err("trailing bytes"),
+    });
+
+    match parsed {
+        Ok(parsed) => Ok(parsed),
+        Err(err) => {
+            log_error!(
+                config,
+                "[{}] Failed to parse CBOR at byte {} due to : {}",
+                header,
+                reader.position,
+                err
+            );
+
+            Err(UserInfoRejection::NotCbor)
+        }
+    }
+}
+
+/// This is a reader of a CBOR value, converting it into its JSON counterpart.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    /// Read the next bytes, failing when there are fewer than `len` remaining ones.
+    fn take(&mut self, len: u64) -> Result<&[u8], &'static str> {
+        let remaining = self.bytes.len() - self.position;
+
+        if len > remaining as u64 {
+            return Err("unexpected end");
+        }
+
+        let start = self.position;
+        self.position += len as usize;
+
+        Ok(&self.bytes[start..self.position])
+    }
+
+    /// Read the argument of the initial byte, `None` being an indefinite length.
+    fn argument(&mut self, info: u8) -> Result<Option<u64>, &'static str> {
+        let argument = match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.take(1)?[0]),
+            25 => u64::from(u16::from_be_bytes(self.take(2)?.try_into().unwrap())),
+            26 => u64::from(u32::from_be_bytes(self.take(4)?.try_into().unwrap())),
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            31 => return Ok(None),
+            _ => return Err("reserved additional information"),
+        };
+
+        Ok(Some(argument))
+    }
+
+    /// Check whether the next byte is the break of an indefinite length item, consuming it if so.
+    fn is_break(&mut self) -> Result<bool, &'static str> {
+        match self.bytes.get(self.position) {
+            Some(0xff) => {
+                self.position += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err("unexpected end"),
+        }
+    }
+
+    /// Read a byte or text string, of the given major type, concatenating the chunks of an indefinite length one.
+    fn string(&mut self, major: u8, len: Option<u64>) -> Result<Vec<u8>, &'static str> {
+        let Some(len) = len else {
+            let mut string = Vec::new();
+
+            while !self.is_break()? {
+                let initial = self.take(1)?[0];
+
+                if initial >> 5 != major || initial & 0x1f == 31 {
+                    return Err("invalid string chunk");
+                }
+
+                let len = self.argument(initial & 0x1f)?;
+                string.extend(self.string(major, len)?);
+            }
+
+            return Ok(string);
+        };
+
+        self.take(len).map(<[u8]>::to_vec)
+    }
+
+    /// Read the next value, at the given nesting depth.
+    fn value(&mut self, depth: usize) -> Result<Value, &'static str> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep");
+        }
+
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        if major == 7 {
+            return self.simple(info);
+        }
+
+        let argument = self.argument(info)?;
+
+        match (major, argument) {
+            (0, Some(unsigned)) => Ok(Value::from(unsigned)),
+            (1, Some(negative)) => i64::try_from(negative)
+                .map(|negative| Value::from(-1 - negative))
+                .map_err(|_| "negative integer out of range"),
+            (2, len) => Ok(Value::Array(
+                self.string(2, len)?.into_iter().map(Value::from).collect(),
+            )),
+            (3, len) => String::from_utf8(self.string(3, len)?)
+                .map(Value::String)
+                .map_err(|_| "invalid UTF-8 text"),
+            (4, len) => {
+                let mut values = Vec::new();
+
+                while self.has_next(len, values.len())? {
+                    values.push(self.value(depth + 1)?);
+                }
+
+                Ok(Value::Array(values))
+            }
+            (5, len) => {
+                let mut claims = Map::new();
+
+                while self.has_next(len, claims.len())? {
+                    let Value::String(key) = self.value(depth + 1)? else {
+                        return Err("map key not being a text");
+                    };
+
+                    claims.insert(key, self.value(depth + 1)?);
+                }
+
+                Ok(Value::Object(claims))
+            }
+            (6, Some(_)) => self.value(depth + 1),
+            _ => Err("invalid indefinite length"),
+        }
+    }
+
+    /// Check whether the array or map, of the given length, has a next item after the `read` ones.
+    fn has_next(&mut self, len: Option<u64>, read: usize) -> Result<bool, &'static str> {
+        match len {
+            Some(len) => Ok((read as u64) < len),
+            None => Ok(!self.is_break()?),
+        }
+    }
+
+    /// Read a simple value or a float.
+    fn simple(&mut self, info: u8) -> Result<Value, &'static str> {
+        let float = match info {
+            20 => return Ok(Value::Bool(false)),
+            21 => return Ok(Value::Bool(true)),
+            22 | 23 => return Ok(Value::Null),
+            25 => half_to_f64(u16::from_be_bytes(self.take(2)?.try_into().unwrap())),
+            26 => f64::from(f32::from_be_bytes(self.take(4)?.try_into().unwrap())),
+            27 => f64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            _ => return Err("unsupported simple value"),
+        };
+
+        Number::from_f64(float)
+            .map(Value::Number)
+            .ok_or("float without JSON counterpart")
+    }
+}
+
+/// Convert a half-precision float (IEEE 754 binary16) into a double-precision one.
+fn half_to_f64(half: u16) -> f64 {
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let mantissa = f64::from(half & 0x3ff);
+
+    let value = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent - 25),
+    };
+
+    if half & 0x8000 == 0 { value } else { -value }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExtractCborUserInfo, ExtractUserInfo, UserInfoConfig, UserInfoRejection, fixtures,
+    };
+    use axum::extract::FromRequestParts;
+    use base64::{Engine, engine::general_purpose};
+    use serde_json::json;
+
+    /// This is the base 64 encoded CBOR map of `{"sub":"my-subject","iat":1516239022,"admin":true,"groups":["admin","users"],"score":-10}`.
+    const CBOR_USER_INFO: &str =
+        "pWNzdWJqbXktc3ViamVjdGNpYXQaWl/4rmVhZG1pbvVmZ3JvdXBzgmVhZG1pbmV1c2Vyc2VzY29yZSk=";
+
+    #[tokio::test]
+    async fn test_cbor_extract_cbor_user_info_map() {
+        let mut parts = fixtures::parts(Some(CBOR_USER_INFO), None);
+
+        let ExtractCborUserInfo(user_info) =
+            ExtractCborUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            user_info,
+            json!({
+                "sub": "my-subject",
+                "iat": 1516239022,
+                "admin": true,
+                "groups": ["admin", "users"],
+                "score": -10
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cbor_extract_cbor_user_info_indefinite_and_floats() {
+        // {_ "bytes": h'0102', "half": 1.5, "tags": 1(1516239022), "text": (_ "my-", "name")}
+        let cbor: &[u8] = &[
+            0xbf, 0x65, b'b', b'y', b't', b'e', b's', 0x42, 0x01, 0x02, 0x64, b'h', b'a', b'l',
+            b'f', 0xf9, 0x3e, 0x00, 0x64, b't', b'a', b'g', b's', 0xc1, 0x1a, 0x5a, 0x5f, 0xf8,
+            0xae, 0x64, b't', b'e', b'x', b't', 0x7f, 0x63, b'm', b'y', b'-', 0x64, b'n', b'a',
+            b'm', b'e', 0xff, 0xff,
+        ];
+        let mut parts = fixtures::parts(Some(&general_purpose::STANDARD.encode(cbor)), None);
+
+        let ExtractCborUserInfo(user_info) =
+            ExtractCborUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            user_info,
+            json!({ "bytes": [1, 2], "half": 1.5, "tags": 1516239022, "text": "my-name" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cbor_extract_cbor_user_info_ko_not_cbor() {
+        // A map announcing 2 entries, with only 1.
+        let cbor: &[u8] = &[0xa2, 0x63, b's', b'u', b'b', 0x01];
+        let mut parts = fixtures::parts(Some(&general_purpose::STANDARD.encode(cbor)), None);
+
+        let user_info = ExtractCborUserInfo::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(user_info.unwrap_err(), UserInfoRejection::NotCbor);
+    }
+
+    #[tokio::test]
+    async fn test_cbor_extract_cbor_user_info_validated() {
+        let config = UserInfoConfig::new().require_object(true);
+        let mut parts = fixtures::parts(
+            Some(&general_purpose::STANDARD.encode([0x82, 0x01, 0x02])),
+            Some(config),
+        );
+
+        let user_info = ExtractCborUserInfo::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(user_info.unwrap_err(), UserInfoRejection::NotObject);
+    }
+
+    #[tokio::test]
+    async fn test_cbor_extract_cbor_user_info_cache_per_decoder() {
+        let config = UserInfoConfig::new().cache_extraction(true);
+
+        // A JSON extraction isn't reused by a CBOR one.
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(config.clone()));
+
+        assert!(
+            ExtractUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            ExtractCborUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err(),
+            UserInfoRejection::NotCbor
+        );
+
+        // Nor the other way round.
+        let mut parts = fixtures::parts(Some(CBOR_USER_INFO), Some(config));
+
+        assert!(
+            ExtractCborUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            ExtractUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err(),
+            UserInfoRejection::NotJson { line: 1, column: 1 }
+        );
+    }
+}
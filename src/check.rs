@@ -0,0 +1,479 @@
+#[cfg(feature = "base62")]
+use crate::HeaderEncoding;
+use crate::{EmptyArrayHandling, JsonType, UserInfoConfig};
+use axum::http::HeaderName;
+use std::{fmt, time::Duration};
+
+/// This is the error returned by `validate_config` for a configuration that would misbehave at runtime, naming the faulty option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The header name of the option isn't a valid HTTP header name, e.g. it's empty.
+    InvalidHeaderName {
+        option: &'static str,
+        header: String,
+    },
+    /// The option is set with an empty value, e.g. an empty claim name.
+    EmptyValue { option: &'static str },
+    /// The limit of the option can never be met, e.g. a zero time budget.
+    InvalidLimit { option: &'static str },
+    /// The claims pointer isn't a valid JSON Pointer (RFC 6901), i.e. neither empty nor starting with `/`.
+    InvalidPointer { pointer: String },
+    /// The option contradicts the other one, e.g. an allowlist stripping the claim required by a check.
+    Contradiction {
+        option: &'static str,
+        other: &'static str,
+    },
+    /// The limit of the option is required by the other one, e.g. a cap on the header length for a decoding quadratic in it.
+    MissingLimit {
+        option: &'static str,
+        other: &'static str,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidHeaderName { option, header } => write!(
+                f,
+                "Invalid configuration : header name {:?} of `{}` not valid",
+                header, option
+            ),
+            ConfigError::EmptyValue { option } => {
+                write!(f, "Invalid configuration : `{}` empty", option)
+            }
+            ConfigError::InvalidLimit { option } => {
+                write!(f, "Invalid configuration : limit of `{}` never met", option)
+            }
+            ConfigError::InvalidPointer { pointer } => write!(
+                f,
+                "Invalid configuration : claims pointer {:?} not a valid JSON Pointer",
+                pointer
+            ),
+            ConfigError::Contradiction { option, other } => write!(
+                f,
+                "Invalid configuration : `{}` contradicting `{}`",
+                option, other
+            ),
+            ConfigError::MissingLimit { option, other } => write!(
+                f,
+                "Invalid configuration : limit of `{}` required by `{}`",
+                option, other
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Validate the configuration, returning the first issue found, so a misconfiguration fails at startup rather than at the first request.
+/// It checks the header names, the empty values, the limits never met or missing and the contradicting options.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use user_info_middleware::{ConfigError, UserInfoConfig, validate_config};
+///
+/// let config = UserInfoConfig::new().time_budget(Duration::ZERO);
+///
+/// assert_eq!(
+///     validate_config(&config),
+///     Err(ConfigError::InvalidLimit { option: "time_budget" })
+/// );
+/// ```
+pub fn validate_config(config: &UserInfoConfig) -> Result<(), ConfigError> {
+    if let Some(trust_boundary) = &config.trust_boundary {
+        if HeaderName::from_bytes(trust_boundary.header.as_bytes()).is_err() {
+            return Err(ConfigError::InvalidHeaderName {
+                option: "trust_boundary",
+                header: trust_boundary.header.clone(),
+            });
+        }
+
+        if trust_boundary.secret.is_empty() {
+            return Err(ConfigError::EmptyValue {
+                option: "trust_boundary",
+            });
+        }
+
+        if config.bearer_subject_check && !trust_boundary.allowed_claims.contains("sub") {
+            return Err(ConfigError::Contradiction {
+                option: "trust_boundary",
+                other: "bearer_subject_check",
+            });
+        }
+    }
+
+    if let Some(signature) = &config.signature {
+        if HeaderName::from_bytes(signature.header.as_bytes()).is_err() {
+            return Err(ConfigError::InvalidHeaderName {
+                option: "hmac_signature",
+                header: signature.header.clone(),
+            });
+        }
+
+        if signature.key.is_empty() {
+            return Err(ConfigError::EmptyValue {
+                option: "hmac_signature",
+            });
+        }
+
+        // A single signature can't cover the comma-separated identities, each one would mismatch it.
+        if config.multi_identity.is_some() {
+            return Err(ConfigError::Contradiction {
+                option: "hmac_signature",
+                other: "multi_identity",
+            });
+        }
+
+        // Nor the client certificate identities, which are always rejected.
+        if config.xfcc_fallback {
+            return Err(ConfigError::Contradiction {
+                option: "hmac_signature",
+                other: "xfcc_fallback",
+            });
+        }
+    }
+
+    if let Some(header) = &config.content_type_header
+        && HeaderName::from_bytes(header.as_bytes()).is_err()
+    {
+        return Err(ConfigError::InvalidHeaderName {
+            option: "content_type_header",
+            header: header.clone(),
+        });
+    }
+
+    if let Some(header) = &config.subject_hash_header
+        && HeaderName::from_bytes(header.as_bytes()).is_err()
+    {
+        return Err(ConfigError::InvalidHeaderName {
+            option: "subject_hash_header",
+            header: header.clone(),
+        });
+    }
+
+    if let Some((tenant, user)) = &config.tenant_user_claims
+        && (tenant.is_empty() || user.is_empty())
+    {
+        return Err(ConfigError::EmptyValue {
+            option: "tenant_user_claims",
+        });
+    }
+
+    if config.owner_path_param.as_deref() == Some("") {
+        return Err(ConfigError::EmptyValue {
+            option: "owner_path_param",
+        });
+    }
+
+    if config.query_param.as_deref() == Some("") {
+        return Err(ConfigError::EmptyValue {
+            option: "query_param",
+        });
+    }
+
+    if config.tenant_id_alias.as_deref() == Some("") {
+        return Err(ConfigError::EmptyValue {
+            option: "tenant_id_alias",
+        });
+    }
+
+    if config.span_field == Some("") {
+        return Err(ConfigError::EmptyValue {
+            option: "span_field",
+        });
+    }
+
+    if let Some(audiences) = &config.expected_audiences
+        && audiences.load().is_empty()
+    {
+        return Err(ConfigError::EmptyValue {
+            option: "expected_audiences",
+        });
+    }
+
+    if config.time_budget == Some(Duration::ZERO) {
+        return Err(ConfigError::InvalidLimit {
+            option: "time_budget",
+        });
+    }
+
+    if config.max_header_len == Some(0) {
+        return Err(ConfigError::InvalidLimit {
+            option: "max_header_len",
+        });
+    }
+
+    if config.trailer_body_limit == Some(0) {
+        return Err(ConfigError::InvalidLimit {
+            option: "trailer_body_limit",
+        });
+    }
+
+    if config.max_decoded_len == Some(0) {
+        return Err(ConfigError::InvalidLimit {
+            option: "max_decoded_len",
+        });
+    }
+
+    if config
+        .parse_guard
+        .is_some_and(|(max_depth, max_values)| max_depth == 0 || max_values == 0)
+    {
+        return Err(ConfigError::InvalidLimit {
+            option: "parse_guard",
+        });
+    }
+
+    if config.max_age == Some(Duration::ZERO) {
+        return Err(ConfigError::InvalidLimit { option: "max_age" });
+    }
+
+    if config
+        .heavy_decodes
+        .as_ref()
+        .is_some_and(|heavy_decodes| heavy_decodes.max_concurrent == 0)
+    {
+        return Err(ConfigError::InvalidLimit {
+            option: "heavy_decodes",
+        });
+    }
+
+    if let Some(pointer) = &config.claims_pointer
+        && !pointer.is_empty()
+        && !pointer.starts_with('/')
+    {
+        return Err(ConfigError::InvalidPointer {
+            pointer: pointer.clone(),
+        });
+    }
+
+    if config.lenient_base64 && config.base64_length_check {
+        return Err(ConfigError::Contradiction {
+            option: "lenient_base64",
+            other: "base64_length_check",
+        });
+    }
+
+    if let Some(types) = &config.accepted_types {
+        if types.is_empty() {
+            return Err(ConfigError::EmptyValue {
+                option: "accepted_types",
+            });
+        }
+
+        if config.require_object && !types.contains(&JsonType::Object) {
+            return Err(ConfigError::Contradiction {
+                option: "accepted_types",
+                other: "require_object",
+            });
+        }
+    }
+
+    #[cfg(feature = "base62")]
+    if config.encoding == HeaderEncoding::Base62 && config.max_header_len.is_none() {
+        return Err(ConfigError::MissingLimit {
+            option: "max_header_len",
+            other: "encoding",
+        });
+    }
+
+    if config.require_object && config.empty_array == Some(EmptyArrayHandling::Accept) {
+        return Err(ConfigError::Contradiction {
+            option: "empty_array",
+            other: "require_object",
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConfigError, EmptyArrayHandling, HeavyDecodeOverflow, JsonType, MultiIdentityHandling,
+        SharedAudiences, UserInfoConfig, validate_config,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_check_validate_config_valid() {
+        let config = UserInfoConfig::new()
+            .require_object(true)
+            .empty_array(EmptyArrayHandling::EmptyClaims)
+            .expected_audiences(SharedAudiences::new(["my-audience"]))
+            .trust_boundary("X-Internal-Secret", "my-secret", ["sub", "email"])
+            .bearer_subject_check(true)
+            .tenant_user_claims("tenant_id", "sub")
+            .time_budget(Duration::from_millis(50))
+            .heavy_decodes(4096, 4, HeavyDecodeOverflow::Reject)
+            .claims_pointer("/claims");
+
+        assert_eq!(validate_config(&config), Ok(()));
+        assert_eq!(validate_config(&UserInfoConfig::new()), Ok(()));
+    }
+
+    #[test]
+    fn test_check_validate_config_invalid_header_name() {
+        let config = UserInfoConfig::new().trust_boundary("", "my-secret", ["sub"]);
+
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigError::InvalidHeaderName {
+                option: "trust_boundary",
+                header: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_contradicting_allowlist() {
+        let config = UserInfoConfig::new()
+            .trust_boundary("X-Internal-Secret", "my-secret", ["email"])
+            .bearer_subject_check(true);
+
+        let error = validate_config(&config).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid configuration : `trust_boundary` contradicting `bearer_subject_check`"
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_invalid_limits() {
+        let heavy_decodes = UserInfoConfig::new().heavy_decodes(4096, 0, HeavyDecodeOverflow::Wait);
+        let max_age = UserInfoConfig::new().max_age(Duration::ZERO);
+        let trailer_body_limit = UserInfoConfig::new().trailer_body_limit(0);
+
+        assert_eq!(
+            validate_config(&heavy_decodes),
+            Err(ConfigError::InvalidLimit {
+                option: "heavy_decodes"
+            })
+        );
+        assert_eq!(
+            validate_config(&max_age),
+            Err(ConfigError::InvalidLimit { option: "max_age" })
+        );
+        assert_eq!(
+            validate_config(&trailer_body_limit),
+            Err(ConfigError::InvalidLimit {
+                option: "trailer_body_limit"
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_empty_values() {
+        let audiences =
+            UserInfoConfig::new().expected_audiences(SharedAudiences::new(Vec::<String>::new()));
+        let tenant_user = UserInfoConfig::new().tenant_user_claims("", "sub");
+
+        assert_eq!(
+            validate_config(&audiences),
+            Err(ConfigError::EmptyValue {
+                option: "expected_audiences"
+            })
+        );
+        assert_eq!(
+            validate_config(&tenant_user),
+            Err(ConfigError::EmptyValue {
+                option: "tenant_user_claims"
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_invalid_pointer_and_contradiction() {
+        let pointer = UserInfoConfig::new().claims_pointer("claims");
+        let empty_array = UserInfoConfig::new()
+            .require_object(true)
+            .empty_array(EmptyArrayHandling::Accept);
+
+        assert_eq!(
+            validate_config(&pointer),
+            Err(ConfigError::InvalidPointer {
+                pointer: "claims".to_string()
+            })
+        );
+        assert_eq!(
+            validate_config(&empty_array),
+            Err(ConfigError::Contradiction {
+                option: "empty_array",
+                other: "require_object"
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_hmac_signature_with_multi_identity() {
+        let config = UserInfoConfig::new()
+            .hmac_signature("X-UserInfo-Signature", "my-key")
+            .multi_identity(MultiIdentityHandling::SkipInvalid);
+
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigError::Contradiction {
+                option: "hmac_signature",
+                other: "multi_identity"
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_hmac_signature_with_xfcc_fallback() {
+        let config = UserInfoConfig::new()
+            .hmac_signature("X-UserInfo-Signature", "my-key")
+            .xfcc_fallback(true);
+
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigError::Contradiction {
+                option: "hmac_signature",
+                other: "xfcc_fallback"
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_validate_config_accepted_types() {
+        let empty = UserInfoConfig::new().accepted_types([]);
+        let contradiction = UserInfoConfig::new()
+            .require_object(true)
+            .accepted_types([JsonType::Array]);
+
+        assert_eq!(
+            validate_config(&empty),
+            Err(ConfigError::EmptyValue {
+                option: "accepted_types"
+            })
+        );
+        assert_eq!(
+            validate_config(&contradiction),
+            Err(ConfigError::Contradiction {
+                option: "accepted_types",
+                other: "require_object"
+            })
+        );
+    }
+
+    #[cfg(feature = "base62")]
+    #[test]
+    fn test_check_validate_config_base62_without_max_header_len() {
+        use crate::HeaderEncoding;
+
+        let config = UserInfoConfig::new().encoding(HeaderEncoding::Base62);
+
+        assert_eq!(
+            validate_config(&config),
+            Err(ConfigError::MissingLimit {
+                option: "max_header_len",
+                other: "encoding",
+            })
+        );
+        assert_eq!(validate_config(&config.max_header_len(4096)), Ok(()));
+    }
+}
@@ -0,0 +1,671 @@
+use crate::{ExtractUserInfo, transform::merge_patch};
+#[cfg(feature = "url")]
+use axum::http::Uri;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+impl ExtractUserInfo {
+    /// Get the claims, as a plain JSON value, e.g. for a generic code working with `Value` directly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+    ///
+    /// assert_eq!(user_info.value(), &json!({ "sub": "my-subject" }));
+    /// ```
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Take the claims, as a plain JSON value, e.g. to store them as a `Box<dyn Any>` without the extractor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+    ///
+    /// assert_eq!(user_info.into_value(), json!({ "sub": "my-subject" }));
+    /// ```
+    pub fn into_value(self) -> Value {
+        self.0
+    }
+
+    /// Get the `groups` claim, as sent by enterprise identity providers such as Azure AD or Okta.
+    /// It handles either an array of strings, ignoring its non string elements, or a single string.
+    /// If the `groups` claim is missing, it returns an empty list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "groups": ["admin", "users"] }));
+    ///
+    /// assert_eq!(user_info.groups(), vec!["admin", "users"]);
+    /// ```
+    pub fn groups(&self) -> Vec<&str> {
+        match self.0.get("groups") {
+            Some(Value::Array(groups)) => groups.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(group)) => vec![group.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the granted scopes, from both the `scope` and `scp` claims, each either a space-delimited string or an array of strings.
+    /// As a set, it enables checks such as `required.is_subset(&granted)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::BTreeSet;
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "scope": "read write" }));
+    /// let required = BTreeSet::from(["read".to_string()]);
+    ///
+    /// assert!(required.is_subset(&user_info.scopes()));
+    /// ```
+    pub fn scopes(&self) -> BTreeSet<String> {
+        ["scope", "scp"]
+            .iter()
+            .filter_map(|key| self.0.get(key))
+            .flat_map(|scopes| match scopes {
+                Value::String(scopes) => {
+                    scopes.split_whitespace().map(ToString::to_string).collect()
+                }
+                Value::Array(scopes) => scopes
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(ToString::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Get a claim, or the given default when it's missing, e.g. for the optional claims.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::{json, Value};
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+    ///
+    /// assert_eq!(user_info.claim_or("locale", &json!("en")), &json!("en"));
+    /// ```
+    pub fn claim_or<'a>(&'a self, key: &str, default: &'a Value) -> &'a Value {
+        self.0.get(key).unwrap_or(default)
+    }
+
+    /// Flatten the claims into a map of dotted keys, e.g. `address.country`, for analytics ingestion.
+    /// The keys of the nested objects are joined with a `.` and the indexes of the arrays are bracketed, e.g. `groups[0]` or `roles[1].name`.
+    /// The keys aren't escaped, so a key having a `.` or a `[` is ambiguous, and the empty objects and arrays are kept as values.
+    /// A user info not being an object, or being an empty one, is flattened into an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(json!({ "address": { "country": "FR" }, "groups": ["admin"] }));
+    ///
+    /// let flattened = user_info.flatten();
+    ///
+    /// assert_eq!(flattened["address.country"], json!("FR"));
+    /// assert_eq!(flattened["groups[0]"], json!("admin"));
+    /// ```
+    pub fn flatten(&self) -> BTreeMap<String, Value> {
+        let mut flattened = BTreeMap::new();
+
+        let Value::Object(claims) = &self.0 else {
+            return flattened;
+        };
+
+        for (claim, value) in claims {
+            flatten_at(value, claim.clone(), &mut flattened);
+        }
+
+        flattened
+    }
+
+    /// Get the top-level claims as strings, e.g. for a templating engine only accepting string values.
+    /// The strings are kept as is, the numbers, booleans and `null` are rendered as their text form and the nested claims are encoded as JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(json!({ "sub": "my-subject", "admin": true, "groups": ["admin"] }));
+    ///
+    /// let strings = user_info.as_string_map();
+    ///
+    /// assert_eq!(strings["admin"], "true");
+    /// assert_eq!(strings["groups"], r#"["admin"]"#);
+    /// ```
+    pub fn as_string_map(&self) -> BTreeMap<String, String> {
+        let Value::Object(claims) = &self.0 else {
+            return BTreeMap::new();
+        };
+
+        claims
+            .iter()
+            .map(|(key, claim)| {
+                let value = match claim {
+                    Value::String(value) => value.clone(),
+                    _ => claim.to_string(),
+                };
+
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    /// Get the `picture` claim, i.e. the URL of the profile picture of the user, e.g. to render an avatar.
+    pub fn picture(&self) -> Option<&str> {
+        self.0.get("picture").and_then(Value::as_str)
+    }
+
+    /// Get the `profile` claim, i.e. the URL of the profile page of the user.
+    pub fn profile(&self) -> Option<&str> {
+        self.0.get("profile").and_then(Value::as_str)
+    }
+
+    /// Get the audiences, from the `aud` claim, either a string or an array of strings.
+    /// A non-standard object, as sent by some misbehaving gateways, is handled as the array of its string values, see `UserInfoConfig::audience_object` to reject it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "aud": "my-audience" }));
+    ///
+    /// assert_eq!(user_info.audience(), vec!["my-audience"]);
+    /// ```
+    pub fn audience(&self) -> Vec<&str> {
+        match self.0.get("aud") {
+            Some(Value::String(audience)) => vec![audience.as_str()],
+            Some(Value::Array(audiences)) => audiences.iter().filter_map(Value::as_str).collect(),
+            Some(Value::Object(audiences)) => {
+                audiences.values().filter_map(Value::as_str).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the `jti` claim, i.e. the unique id of the token, e.g. for a replay cache, see `UserInfoConfig::require_jti` to require it.
+    pub fn jti(&self) -> Option<&str> {
+        self.0.get("jti").and_then(Value::as_str)
+    }
+
+    /// Get the `tid` claim, i.e. the tenant id sent by Azure AD, see `UserInfoConfig::tenant_id_alias` to map another tenant claim onto it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "tid": "my-tenant" }));
+    ///
+    /// assert_eq!(user_info.tenant_id(), Some("my-tenant"));
+    /// ```
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.0.get("tid").and_then(Value::as_str)
+    }
+
+    /// Get an integer claim as an `i128`, e.g. a 20-digit `account_number` beyond the `i64` range.
+    /// Any integer within the `u64` range is exact. Beyond, serde_json parses an integer as a `f64`, losing its precision, so the claim is `None` rather than a rounded value, unless the `arbitrary_precision` feature is enabled, keeping any integer within the `i128` range exact.
+    /// A float, a string or an integer beyond the `i128` range is `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "account_number": 12345678901234567890u64 }));
+    ///
+    /// assert_eq!(user_info.claim_as_i128("account_number"), Some(12345678901234567890));
+    /// ```
+    pub fn claim_as_i128(&self, key: &str) -> Option<i128> {
+        let number = self.0.get(key)?.as_number()?;
+
+        if let Some(number) = number.as_i64() {
+            return Some(number.into());
+        }
+
+        if let Some(number) = number.as_u64() {
+            return Some(number.into());
+        }
+
+        number.to_string().parse().ok()
+    }
+
+    /// Get the `picture` claim, only if it's a valid absolute `http` or `https` URL.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "picture": "javascript:alert(1)" }));
+    ///
+    /// assert_eq!(user_info.picture(), Some("javascript:alert(1)"));
+    /// assert_eq!(user_info.picture_url(), None);
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn picture_url(&self) -> Option<&str> {
+        self.picture().filter(|picture| is_http_url(picture))
+    }
+
+    /// Get the `profile` claim, only if it's a valid absolute `http` or `https` URL.
+    #[cfg(feature = "url")]
+    pub fn profile_url(&self) -> Option<&str> {
+        self.profile().filter(|profile| is_http_url(profile))
+    }
+
+    /// Apply a JSON Merge Patch (RFC 7386) onto the user info, e.g. to overlay server-side attributes before forwarding it.
+    /// A key set to `null` in the patch is removed, any other key is added or overwritten, recursively for the nested objects.
+    ///
+    /// # Links
+    ///
+    /// https://www.rfc-editor.org/rfc/rfc7386
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let mut user_info = ExtractUserInfo(serde_json::json!({ "sub": "my-subject", "name": "my-name" }));
+    ///
+    /// user_info.merge(&serde_json::json!({ "name": null, "tenant": "my-tenant" }));
+    ///
+    /// assert_eq!(user_info.0, serde_json::json!({ "sub": "my-subject", "tenant": "my-tenant" }));
+    /// ```
+    pub fn merge(&mut self, patch: &Value) {
+        merge_patch(&mut self.0, patch);
+    }
+}
+
+fn flatten_at(value: &Value, key: String, flattened: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(claims) if !claims.is_empty() => {
+            for (claim, value) in claims {
+                flatten_at(value, format!("{}.{}", key, claim), flattened);
+            }
+        }
+        Value::Array(values) if !values.is_empty() => {
+            for (index, value) in values.iter().enumerate() {
+                flatten_at(value, format!("{}[{}]", key, index), flattened);
+            }
+        }
+        value => {
+            flattened.insert(key, value.clone());
+        }
+    }
+}
+
+/// Check whether the string is a valid absolute `http` or `https` URL, with a host.
+#[cfg(feature = "url")]
+fn is_http_url(url: &str) -> bool {
+    url.parse::<Uri>().is_ok_and(|url| {
+        matches!(url.scheme_str(), Some("http" | "https"))
+            && url.host().is_some_and(|host| !host.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractUserInfo, fixtures};
+    use serde_json::{Value, json};
+    use std::any::Any;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn test_claims_groups_array() {
+        let user_info = ExtractUserInfo(json!({ "groups": ["admin", "users", 1] }));
+
+        assert_eq!(user_info.groups(), vec!["admin", "users"]);
+    }
+
+    #[test]
+    fn test_claims_groups_single_string() {
+        let user_info = ExtractUserInfo(json!({ "groups": "admin" }));
+
+        assert_eq!(user_info.groups(), vec!["admin"]);
+    }
+
+    #[test]
+    fn test_claims_groups_missing() {
+        let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+
+        assert!(user_info.groups().is_empty());
+    }
+
+    #[test]
+    fn test_claims_audience() {
+        assert_eq!(
+            ExtractUserInfo(fixtures::user_info()).audience(),
+            vec!["my-audience"]
+        );
+        assert_eq!(
+            ExtractUserInfo(json!({ "aud": ["a", 1, "b"] })).audience(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            ExtractUserInfo(json!({ "aud": { "0": "a", "1": "b" } })).audience(),
+            vec!["a", "b"]
+        );
+        assert!(
+            ExtractUserInfo(json!({ "sub": "my-subject" }))
+                .audience()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_claims_jti() {
+        assert_eq!(
+            ExtractUserInfo(fixtures::user_info()).jti(),
+            Some("my-unique-id")
+        );
+        assert_eq!(ExtractUserInfo(json!({ "sub": "my-subject" })).jti(), None);
+    }
+
+    #[test]
+    fn test_claims_tenant_id_present() {
+        let user_info = ExtractUserInfo(json!({ "tid": "my-tenant", "sub": "my-subject" }));
+
+        assert_eq!(user_info.tenant_id(), Some("my-tenant"));
+    }
+
+    #[test]
+    fn test_claims_tenant_id_missing() {
+        let user_info = ExtractUserInfo(json!({ "tenant": "my-tenant", "sub": "my-subject" }));
+
+        assert_eq!(user_info.tenant_id(), None);
+    }
+
+    #[test]
+    fn test_claims_merge_add() {
+        let mut user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+
+        user_info.merge(&json!({ "tenant": { "id": "my-tenant" } }));
+
+        assert_eq!(
+            user_info.0,
+            json!({ "sub": "my-subject", "tenant": { "id": "my-tenant" } })
+        );
+    }
+
+    #[test]
+    fn test_claims_merge_overwrite() {
+        let mut user_info =
+            ExtractUserInfo(json!({ "sub": "my-subject", "tenant": { "id": "a", "name": "b" } }));
+
+        user_info.merge(&json!({ "sub": "my-other-subject", "tenant": { "id": "c" } }));
+
+        assert_eq!(
+            user_info.0,
+            json!({ "sub": "my-other-subject", "tenant": { "id": "c", "name": "b" } })
+        );
+    }
+
+    #[test]
+    fn test_claims_merge_null_removal() {
+        let mut user_info =
+            ExtractUserInfo(json!({ "sub": "my-subject", "tenant": { "id": "a", "name": "b" } }));
+
+        user_info.merge(&json!({ "tenant": { "name": null }, "missing": null }));
+
+        assert_eq!(
+            user_info.0,
+            json!({ "sub": "my-subject", "tenant": { "id": "a" } })
+        );
+    }
+
+    #[test]
+    fn test_claims_scopes_space_delimited() {
+        let user_info = ExtractUserInfo(json!({ "scope": "read  write read" }));
+
+        assert_eq!(
+            user_info.scopes(),
+            BTreeSet::from(["read".to_string(), "write".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_claims_scopes_array() {
+        let user_info = ExtractUserInfo(json!({ "scp": ["read", "write", 1], "scope": "admin" }));
+
+        assert_eq!(
+            user_info.scopes(),
+            BTreeSet::from(["admin".to_string(), "read".to_string(), "write".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_claims_scopes_missing() {
+        let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+
+        assert!(user_info.scopes().is_empty());
+    }
+
+    #[test]
+    fn test_claims_claim_or_present() {
+        let user_info = ExtractUserInfo(json!({ "locale": "fr" }));
+
+        assert_eq!(user_info.claim_or("locale", &json!("en")), &json!("fr"));
+    }
+
+    #[test]
+    fn test_claims_claim_or_absent() {
+        let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+
+        assert_eq!(user_info.claim_or("locale", &json!("en")), &json!("en"));
+    }
+
+    #[test]
+    fn test_claims_as_string_map_scalars() {
+        let user_info = ExtractUserInfo(json!({
+            "sub": "my-subject",
+            "iat": 1516239022,
+            "score": 0.5,
+            "admin": false,
+            "locale": null
+        }));
+
+        assert_eq!(
+            user_info.as_string_map(),
+            BTreeMap::from([
+                ("admin".to_string(), "false".to_string()),
+                ("iat".to_string(), "1516239022".to_string()),
+                ("locale".to_string(), "null".to_string()),
+                ("score".to_string(), "0.5".to_string()),
+                ("sub".to_string(), "my-subject".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_claims_as_string_map_nested() {
+        let user_info = ExtractUserInfo(json!({
+            "address": { "country": "FR", "city": "Paris" },
+            "groups": ["admin", "users"]
+        }));
+
+        let strings = user_info.as_string_map();
+
+        assert_eq!(strings["address"], r#"{"city":"Paris","country":"FR"}"#);
+        assert_eq!(strings["groups"], r#"["admin","users"]"#);
+    }
+
+    #[test]
+    fn test_claims_flatten_nested_objects() {
+        let user_info = ExtractUserInfo(json!({
+            "sub": "my-subject",
+            "address": { "country": "FR", "geo": { "lat": 48.85 } },
+            "empty": {}
+        }));
+
+        assert_eq!(
+            user_info.flatten(),
+            BTreeMap::from([
+                ("address.country".to_string(), json!("FR")),
+                ("address.geo.lat".to_string(), json!(48.85)),
+                ("empty".to_string(), json!({})),
+                ("sub".to_string(), json!("my-subject")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_claims_flatten_arrays() {
+        let user_info = ExtractUserInfo(json!({
+            "groups": ["admin", "users"],
+            "roles": [{ "name": "owner" }, []]
+        }));
+
+        assert_eq!(
+            user_info.flatten(),
+            BTreeMap::from([
+                ("groups[0]".to_string(), json!("admin")),
+                ("groups[1]".to_string(), json!("users")),
+                ("roles[0].name".to_string(), json!("owner")),
+                ("roles[1]".to_string(), json!([])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_claims_flatten_empty_or_not_an_object() {
+        assert_eq!(ExtractUserInfo::default().flatten(), BTreeMap::new());
+        assert_eq!(ExtractUserInfo(json!({})).flatten(), BTreeMap::new());
+        assert_eq!(ExtractUserInfo(json!(["admin"])).flatten(), BTreeMap::new());
+        assert_eq!(ExtractUserInfo(json!("admin")).flatten(), BTreeMap::new());
+    }
+
+    #[test]
+    fn test_claims_flatten_empty_keys() {
+        let user_info = ExtractUserInfo(json!({
+            "": { "sub": "my-subject" },
+            "sub": "my-other-subject",
+            "address": { "": "FR" }
+        }));
+
+        assert_eq!(
+            user_info.flatten(),
+            BTreeMap::from([
+                (".sub".to_string(), json!("my-subject")),
+                ("address.".to_string(), json!("FR")),
+                ("sub".to_string(), json!("my-other-subject")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_claims_picture() {
+        let user_info = ExtractUserInfo(json!({ "picture": "https://example.com/me.png" }));
+
+        assert_eq!(user_info.picture(), Some("https://example.com/me.png"));
+        assert_eq!(ExtractUserInfo::default().picture(), None);
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_claims_picture_url_valid() {
+        let user_info = ExtractUserInfo(json!({
+            "picture": "https://example.com/me.png",
+            "profile": "http://example.com/me"
+        }));
+
+        assert_eq!(user_info.picture_url(), Some("https://example.com/me.png"));
+        assert_eq!(user_info.profile_url(), Some("http://example.com/me"));
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_claims_picture_url_invalid() {
+        for picture in [
+            "not a url",
+            "ftp://example.com/me.png",
+            "/me.png",
+            "https://",
+        ] {
+            let user_info = ExtractUserInfo(json!({ "picture": picture }));
+
+            assert_eq!(user_info.picture_url(), None, "{}", picture);
+        }
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_claims_picture_url_missing() {
+        let user_info = ExtractUserInfo(json!({ "picture": 1 }));
+
+        assert_eq!(user_info.picture_url(), None);
+        assert_eq!(user_info.profile_url(), None);
+    }
+
+    #[test]
+    fn test_claims_value_and_into_value() {
+        let user_info = ExtractUserInfo(fixtures::user_info());
+
+        assert_eq!(user_info.value(), &fixtures::user_info());
+
+        let stored: Box<dyn Any> = Box::new(user_info.into_value());
+
+        assert_eq!(stored.downcast_ref::<Value>(), Some(&fixtures::user_info()));
+    }
+
+    #[test]
+    fn test_claims_claim_as_i128_within_u64() {
+        let user_info = ExtractUserInfo(
+            serde_json::from_str(
+                r#"{ "small": -42, "large": 12345678901234567890, "float": 1.5, "string": "42" }"#,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(user_info.claim_as_i128("small"), Some(-42));
+        assert_eq!(user_info.claim_as_i128("large"), Some(12345678901234567890));
+        assert_eq!(user_info.claim_as_i128("float"), None);
+        assert_eq!(user_info.claim_as_i128("string"), None);
+        assert_eq!(user_info.claim_as_i128("missing"), None);
+    }
+
+    #[test]
+    fn test_claims_claim_as_i128_beyond_u64() {
+        let user_info = ExtractUserInfo(
+            serde_json::from_str(
+                r#"{ "positive": 123456789012345678901, "negative": -123456789012345678901 }"#,
+            )
+            .unwrap(),
+        );
+
+        if cfg!(feature = "arbitrary_precision") {
+            assert_eq!(
+                user_info.claim_as_i128("positive"),
+                Some(123456789012345678901)
+            );
+            assert_eq!(
+                user_info.claim_as_i128("negative"),
+                Some(-123456789012345678901)
+            );
+        } else {
+            assert_eq!(user_info.claim_as_i128("positive"), None);
+            assert_eq!(user_info.claim_as_i128("negative"), None);
+        }
+    }
+}
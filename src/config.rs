@@ -0,0 +1,938 @@
+use crate::{
+    DEFAULT_SENSITIVE_KEYS, ExtractionEvent, SharedAudiences, SubjectFormat, Transform,
+    UserInfoRejection, events::EventSender, signature::DetachedSignature, trust::TrustBoundary,
+};
+use axum::http::{Extensions, StatusCode};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::sync::{Semaphore, mpsc::Sender};
+
+/// This is the configuration of the `X-Endpoint-API-UserInfo` extraction.
+/// It's read from the request extensions, so it can be provided to every route via an `Extension` layer.
+/// If no configuration is present in the request extensions, the default configuration is used.
+///
+/// # Links
+///
+/// https://docs.rs/axum/latest/axum/struct.Extension.html
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Extension, Router};
+/// use user_info_middleware::{ExtractUserInfo, UserInfoConfig};
+///
+/// async fn handler(ExtractUserInfo(user_info): ExtractUserInfo) {
+///     println!("User Info: {:?}", user_info);
+/// }
+///
+/// let config = UserInfoConfig::new().trace_success(true);
+///
+/// let app = Router::<()>::new()
+///     .route("/foo", get(handler))
+///     .layer(Extension(config));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UserInfoConfig {
+    pub(crate) trace_success: bool,
+    pub(crate) require_object: bool,
+    pub(crate) empty_array: Option<EmptyArrayHandling>,
+    pub(crate) max_array_len: Option<usize>,
+    pub(crate) control_characters: Option<ControlCharacterHandling>,
+    pub(crate) expected_audiences: Option<SharedAudiences>,
+    pub(crate) mime_base64: bool,
+    pub(crate) encoding: HeaderEncoding,
+    pub(crate) user_lookup: Option<(UserLookup, StatusCode)>,
+    pub(crate) bearer_subject_check: bool,
+    pub(crate) logging_disabled: bool,
+    pub(crate) cache_extraction: bool,
+    pub(crate) rejection_message: Option<fn(&UserInfoRejection) -> String>,
+    pub(crate) subject_format: Option<SubjectFormat>,
+    pub(crate) sensitive_keys: Option<BTreeSet<String>>,
+    pub(crate) span_field: Option<&'static str>,
+    pub(crate) transforms: Vec<Transform>,
+    pub(crate) claims_pointer: Option<String>,
+    pub(crate) time_budget: Option<Duration>,
+    pub(crate) issuer_denylist: Option<BTreeSet<String>>,
+    pub(crate) shadow_mode: bool,
+    pub(crate) claim_extensions: Vec<(String, ClaimExtension)>,
+    pub(crate) base64_length_check: bool,
+    pub(crate) tenant_user_claims: Option<(String, String)>,
+    pub(crate) reject_expired: bool,
+    pub(crate) expiration_boundary: ExpirationBoundary,
+    pub(crate) header_name_hidden: bool,
+    pub(crate) trust_boundary: Option<TrustBoundary>,
+    pub(crate) events: Option<EventSender>,
+    pub(crate) lenient_types: bool,
+    pub(crate) max_age: Option<Duration>,
+    pub(crate) trailer_fallback: bool,
+    pub(crate) trailer_body_limit: Option<usize>,
+    pub(crate) heavy_decodes: Option<HeavyDecodes>,
+    pub(crate) legacy_messages: bool,
+    pub(crate) multi_identity: Option<MultiIdentityHandling>,
+    pub(crate) owner_path_param: Option<String>,
+    pub(crate) max_header_len: Option<usize>,
+    pub(crate) tenant_id_alias: Option<String>,
+    pub(crate) allowed_keys: Option<BTreeSet<String>>,
+    pub(crate) nonce_check: bool,
+    pub(crate) max_decoded_len: Option<usize>,
+    pub(crate) xfcc_fallback: bool,
+    pub(crate) subject_hash_header: Option<String>,
+    pub(crate) parse_guard: Option<(usize, usize)>,
+    pub(crate) content_type_header: Option<String>,
+    pub(crate) context_claims: Option<Vec<String>>,
+    pub(crate) lenient_base64: bool,
+    pub(crate) require_jti: bool,
+    pub(crate) strip_bom: bool,
+    pub(crate) audience_object: Option<AudienceObjectHandling>,
+    pub(crate) unique_array_claims: Option<BTreeSet<String>>,
+    pub(crate) signature: Option<DetachedSignature>,
+    pub(crate) shape_error_status: Option<StatusCode>,
+    pub(crate) outcome_extension: bool,
+    pub(crate) reject_not_yet_valid: bool,
+    pub(crate) clock_skew: Duration,
+    pub(crate) query_param: Option<String>,
+    pub(crate) accepted_types: Option<BTreeSet<JsonType>>,
+    pub(crate) audit_log: Option<log::Level>,
+}
+
+/// This is the future returned by a user lookup, resolving to whether the user exists.
+pub(crate) type UserLookupFuture = Pin<Box<dyn Future<Output = bool> + Send>>;
+
+/// This is a user lookup, run after the extraction, deciding whether the user exists.
+#[derive(Clone)]
+pub(crate) struct UserLookup(pub(crate) Arc<dyn Fn(&Value) -> UserLookupFuture + Send + Sync>);
+
+impl fmt::Debug for UserLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UserLookup(..)")
+    }
+}
+
+/// This is the function inserting a claim into the request extensions.
+pub(crate) type ClaimExtensionFn = dyn Fn(&Value, &mut Extensions) + Send + Sync;
+
+/// This is a mapping of a claim into the request extensions.
+#[derive(Clone)]
+pub(crate) struct ClaimExtension(pub(crate) Arc<ClaimExtensionFn>);
+
+impl fmt::Debug for ClaimExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ClaimExtension(..)")
+    }
+}
+
+/// This is the handling of a top-level empty JSON array, as sent by some gateways when there are no claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyArrayHandling {
+    /// Accept the empty array as is.
+    Accept,
+    /// Treat the empty array as an empty JSON object, i.e. no claims.
+    EmptyClaims,
+    /// Reject the empty array with a 400 Bad Request.
+    Reject,
+}
+
+/// This is the handling of an `aud` claim being a JSON object, as sent by some misbehaving gateways instead of a string or an array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudienceObjectHandling {
+    /// Reject the object with a 400 Bad Request, whatever the expected audiences.
+    Reject,
+    /// Treat the string values of the object as the audiences, e.g. `{"0":"my-audience"}` as `["my-audience"]`.
+    Values,
+}
+
+/// This is the type of a JSON value, e.g. to accept some top-level types only via `accepted_types`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JsonType {
+    /// A JSON object.
+    Object,
+    /// A JSON array.
+    Array,
+    /// A JSON string.
+    String,
+    /// A JSON number.
+    Number,
+    /// A JSON boolean.
+    Bool,
+    /// The JSON `null`.
+    Null,
+}
+
+impl JsonType {
+    /// Get the type of the JSON value.
+    pub(crate) fn of(value: &Value) -> Self {
+        match value {
+            Value::Object(_) => JsonType::Object,
+            Value::Array(_) => JsonType::Array,
+            Value::String(_) => JsonType::String,
+            Value::Number(_) => JsonType::Number,
+            Value::Bool(_) => JsonType::Bool,
+            Value::Null => JsonType::Null,
+        }
+    }
+}
+
+impl fmt::Display for JsonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JsonType::Object => "object",
+            JsonType::Array => "array",
+            JsonType::String => "string",
+            JsonType::Number => "number",
+            JsonType::Bool => "boolean",
+            JsonType::Null => "null",
+        })
+    }
+}
+
+/// This is the encoding of the header value, before the JSON parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderEncoding {
+    /// The standard base 64 (RFC 4648), as sent by ESPv2.
+    #[default]
+    Base64,
+    /// The base 32 (RFC 4648), case-insensitive and with an optional padding.
+    #[cfg(feature = "base32")]
+    Base32,
+    /// The base 62, i.e. the big-endian number written with the `0-9A-Za-z` digits, the leading `0` digits being leading zero bytes.
+    /// Its decoding is quadratic in the header length, so `validate_config` requires a `max_header_len` with it.
+    #[cfg(feature = "base62")]
+    Base62,
+}
+
+impl HeaderEncoding {
+    /// Get the name of the encoding, as rendered in the rejection messages.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            HeaderEncoding::Base64 => "base 64",
+            #[cfg(feature = "base32")]
+            HeaderEncoding::Base32 => "base 32",
+            #[cfg(feature = "base62")]
+            HeaderEncoding::Base62 => "base 62",
+        }
+    }
+}
+
+/// This is the handling of ASCII control characters, e.g. a newline or a null byte, in string claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCharacterHandling {
+    /// Strip the control characters from the string claims.
+    Strip,
+    /// Reject the user info with a 400 Bad Request.
+    Reject,
+}
+
+/// This is the comparison of the `exp` claim with the current time, i.e. whether a user info is expired at its exact expiration time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpirationBoundary {
+    /// Expired once the current time reaches the `exp` claim, i.e. `now >= exp`, as required by RFC 7519.
+    #[default]
+    Inclusive,
+    /// Expired once the current time passes the `exp` claim, i.e. `now > exp`, so a user info having `exp` equal to `iat` is valid during its issuance second.
+    Exclusive,
+}
+
+/// This is the handling of a heavy decode once the concurrent heavy decodes cap is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeavyDecodeOverflow {
+    /// Wait for a running heavy decode to complete.
+    Wait,
+    /// Reject the user info with a 503 Service Unavailable.
+    Reject,
+}
+
+/// This is the handling of an invalid segment of a comma-separated multi-identity header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiIdentityHandling {
+    /// Reject the whole request with the rejection of the invalid segment.
+    Reject,
+    /// Skip the invalid segment, keeping the valid ones.
+    SkipInvalid,
+}
+
+/// This is the cap on the concurrent heavy decodes, i.e. of the header values longer than the threshold, run on the blocking pool.
+#[derive(Debug, Clone)]
+pub(crate) struct HeavyDecodes {
+    pub(crate) threshold: usize,
+    pub(crate) max_concurrent: usize,
+    pub(crate) permits: Arc<Semaphore>,
+    pub(crate) overflow: HeavyDecodeOverflow,
+}
+
+impl UserInfoConfig {
+    /// Create a new configuration with the default behavior.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log, at trace level, each successful extraction with its subject and claim count.
+    /// The full payload is never logged.
+    pub fn trace_success(mut self, enabled: bool) -> Self {
+        self.trace_success = enabled;
+        self
+    }
+
+    /// Require the decoded user info to be a JSON object, rejecting any other JSON value with a 400 Bad Request.
+    pub fn require_object(mut self, enabled: bool) -> Self {
+        self.require_object = enabled;
+        self
+    }
+
+    /// Set the handling of a top-level empty JSON array.
+    /// If not set, an empty array is rejected when an object is required and accepted otherwise.
+    pub fn empty_array(mut self, handling: EmptyArrayHandling) -> Self {
+        self.empty_array = Some(handling);
+        self
+    }
+
+    /// Reject, with a 400 Bad Request naming its path, any array having more than `max` elements, at any depth.
+    /// It's disabled by default, a generous cap such as `10_000` is advised to bound the memory used by a single claim.
+    pub fn max_array_len(mut self, max: usize) -> Self {
+        self.max_array_len = Some(max);
+        self
+    }
+
+    /// Sanitize the string claims, at any depth, by either stripping or rejecting the ASCII control characters they contain.
+    /// Stripping registers a `Transform::StripControlCharacters`, so it runs in its registration order among the other transforms, while rejecting is a validation, run after all the transforms.
+    /// Calling it again replaces the previous handling, any strip transform already registered being removed first.
+    /// It's disabled by default.
+    pub fn control_characters(mut self, handling: ControlCharacterHandling) -> Self {
+        self.transforms
+            .retain(|transform| !matches!(transform, Transform::StripControlCharacters));
+
+        if handling == ControlCharacterHandling::Strip {
+            self.transforms.push(Transform::StripControlCharacters);
+        }
+
+        self.control_characters = Some(handling);
+        self
+    }
+
+    /// Require the `aud` claim to have at least one of the expected audiences, rejecting otherwise with a 401 Unauthorized.
+    /// The expected audiences are shared, so they can be swapped at runtime without rebuilding the configuration.
+    pub fn expected_audiences(mut self, audiences: SharedAudiences) -> Self {
+        self.expected_audiences = Some(audiences);
+        self
+    }
+
+    /// Decode the header value as a MIME (RFC 2045) base 64, stripping the line breaks, and any other whitespace, before decoding.
+    /// As a header value can't hold a raw line break, it mostly handles line breaks folded into spaces by a middlebox.
+    pub fn mime_base64(mut self, enabled: bool) -> Self {
+        self.mime_base64 = enabled;
+        self
+    }
+
+    /// Decode the header value with the given encoding, e.g. the base 32 of a partner gateway, rather than the base 64 sent by ESPv2.
+    /// The other encodings are behind the `base32` and `base62` features, the decoded value being parsed as JSON as usual.
+    /// As the base 62 decoding is quadratic in the header length, it should be used along with a `max_header_len`, as required by `validate_config`.
+    pub fn encoding(mut self, encoding: HeaderEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Look up the user after the extraction, rejecting with the given status code when the user doesn't exist.
+    /// Using a 401 Unauthorized, rather than a 404 Not Found, centralizes an enumeration-safe policy.
+    /// The lookup gets the extracted user info and returns a future resolving to whether the user exists.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().user_lookup(
+    ///     |user_info| {
+    ///         let sub = user_info["sub"].as_str().map(ToString::to_string);
+    ///
+    ///         async move { sub.as_deref() == Some("my-subject") }
+    ///     },
+    ///     StatusCode::UNAUTHORIZED,
+    /// );
+    /// ```
+    pub fn user_lookup<F, Fut>(mut self, lookup: F, status: StatusCode) -> Self
+    where
+        F: Fn(&Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.user_lookup = Some((
+            UserLookup(Arc::new(move |user_info| Box::pin(lookup(user_info)))),
+            status,
+        ));
+        self
+    }
+
+    /// When both the `X-Endpoint-API-UserInfo` header and an `Authorization` bearer JWT are present, compare their `sub` claims, rejecting a mismatch with a 401 Unauthorized.
+    /// The bearer isn't verified, its payload is only decoded, and an opaque bearer is skipped.
+    /// By default, the `X-Endpoint-API-UserInfo` header takes precedence without any comparison.
+    pub fn bearer_subject_check(mut self, enabled: bool) -> Self {
+        self.bearer_subject_check = enabled;
+        self
+    }
+
+    /// Fully suppress the logs of this crate, whatever their level, e.g. in regulated environments forbidding libraries to log authentication failures.
+    /// It takes precedence over `trace_success`.
+    pub fn disable_logging(mut self, disabled: bool) -> Self {
+        self.logging_disabled = disabled;
+        self
+    }
+
+    /// Cache a successful extraction in the request extensions, so it's reused by the following extractions of the same request, e.g. by a middleware then a handler.
+    /// It's cached per decoder, so an `ExtractCborUserInfo` never reuses the extraction of an `ExtractUserInfo`, nor the other way round.
+    pub fn cache_extraction(mut self, enabled: bool) -> Self {
+        self.cache_extraction = enabled;
+        self
+    }
+
+    /// Format the message of the rejection responses, e.g. to use a localized error catalog rather than the built-in English messages.
+    /// The status code of the responses is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::{UserInfoConfig, UserInfoRejection};
+    ///
+    /// let config = UserInfoConfig::new().rejection_message(|rejection| match rejection {
+    ///     UserInfoRejection::NotFound => "Informations utilisateur introuvables".to_string(),
+    ///     _ => "Informations utilisateur invalides".to_string(),
+    /// });
+    /// ```
+    pub fn rejection_message(mut self, formatter: fn(&UserInfoRejection) -> String) -> Self {
+        self.rejection_message = Some(formatter);
+        self
+    }
+
+    /// Require the `sub` claim to match the given format, e.g. a UUID, rejecting otherwise with a 400 Bad Request.
+    /// A missing or non string `sub` claim is rejected too.
+    pub fn subject_format(mut self, format: SubjectFormat) -> Self {
+        self.subject_format = Some(format);
+        self
+    }
+
+    /// Set the denylist of the sensitive claims, redacted wherever the user info is exposed for debugging.
+    /// It replaces the default denylist, i.e. `DEFAULT_SENSITIVE_KEYS`.
+    pub fn sensitive_keys<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.sensitive_keys = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Record the redacted user info, as a JSON string, in the given field of the current `tracing` span.
+    /// The field must be declared by the span, e.g. via `tracing::field::Empty`, otherwise nothing is recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let span = tracing::info_span!("request", user_info = tracing::field::Empty);
+    /// let config = UserInfoConfig::new().span_field("user_info");
+    /// ```
+    pub fn span_field(mut self, field: &'static str) -> Self {
+        self.span_field = Some(field);
+        self
+    }
+
+    /// Register a transform of the user info, applied after the previously registered ones.
+    /// The transforms run sequentially, in their registration order, right after the parse and the empty array handling, and before any validation.
+    /// So a validation, e.g. `require_object` or `control_characters`, always sees the transformed user info.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Select the claims at the given JSON Pointer (RFC 6901), e.g. `/data/identity/claims`, when they're wrapped in a larger envelope.
+    /// The pointer is applied right after the parse, rejecting with a 400 Bad Request when it doesn't resolve.
+    pub fn claims_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.claims_pointer = Some(pointer.into());
+        self
+    }
+
+    /// Bound the whole extraction, including the async hooks such as the user lookup, to the given time budget.
+    /// Once exceeded, the extraction is aborted and rejected with a 408 Request Timeout.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Reject, with a 403 Forbidden, an `iss` claim being one of the denylisted issuers, e.g. compromised ones.
+    /// It's evaluated independently of any other validation, so a denylisted issuer is always rejected, even when its audience is expected.
+    pub fn issuer_denylist<I, K>(mut self, issuers: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.issuer_denylist = Some(issuers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Run the claim validations, i.e. `require_object`, `accepted_types`, `require_jti`, `allowed_keys`, `unique_array_claims`, `max_array_len`, `control_characters`, `subject_format`, `issuer_denylist`, `expected_audiences`, `reject_expired`, `reject_not_yet_valid` and `max_age`, without enforcing them.
+    /// Every validation runs, each failed one being only logged, at warn level, so the would-be rejection rate can be observed before enabling them.
+    /// The decoding failures, e.g. a missing header or an invalid JSON, are still rejected, as are the request checks out of its scope, i.e. the `content_type_header`, `hmac_signature`, `bearer_subject_check`, `nonce_check` and `user_lookup` ones.
+    /// `VerifiedUserInfo`, only holding validated claims, still enforces them.
+    pub fn shadow_mode(mut self, enabled: bool) -> Self {
+        self.shadow_mode = enabled;
+        self
+    }
+
+    /// Map a claim into the request extensions, e.g. as a typed extension for the downstream layers, applied after the previously registered mappings.
+    /// The mapping is run on a successful extraction, when the claim is present, with the claim value and the request extensions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// #[derive(Clone)]
+    /// struct TenantId(String);
+    ///
+    /// let config = UserInfoConfig::new().claim_extension("tenant", |tenant, extensions| {
+    ///     if let Some(tenant) = tenant.as_str() {
+    ///         extensions.insert(TenantId(tenant.to_string()));
+    ///     }
+    /// });
+    /// ```
+    pub fn claim_extension<F>(mut self, claim: impl Into<String>, insert: F) -> Self
+    where
+        F: Fn(&Value, &mut Extensions) + Send + Sync + 'static,
+    {
+        self.claim_extensions
+            .push((claim.into(), ClaimExtension(Arc::new(insert))));
+        self
+    }
+
+    /// Reject, before decoding, a base 64 value whose length, padding included, isn't a multiple of 4, e.g. a truncated one, with a distinct message.
+    /// In MIME mode, the length is checked once the whitespaces are stripped.
+    pub fn base64_length_check(mut self, enabled: bool) -> Self {
+        self.base64_length_check = enabled;
+        self
+    }
+
+    /// Set the claims the `TenantUser` extractor derives its tenant and user from, i.e. `tenant_id` and `sub` by default.
+    pub fn tenant_user_claims(
+        mut self,
+        tenant: impl Into<String>,
+        user: impl Into<String>,
+    ) -> Self {
+        self.tenant_user_claims = Some((tenant.into(), user.into()));
+        self
+    }
+
+    /// Reject, with a 401 Unauthorized, a user info whose `exp` claim, in seconds since the Unix epoch, is expired.
+    /// A missing or non numeric `exp` claim isn't rejected.
+    pub fn reject_expired(mut self, enabled: bool) -> Self {
+        self.reject_expired = enabled;
+        self
+    }
+
+    /// Set whether `exp == now` counts as expired, i.e. `ExpirationBoundary::Inclusive` by default, as required by RFC 7519.
+    /// `ExpirationBoundary::Exclusive` accepts a user info during its expiration second, e.g. one having `exp` equal to `iat`.
+    pub fn expiration_boundary(mut self, boundary: ExpirationBoundary) -> Self {
+        self.expiration_boundary = boundary;
+        self
+    }
+
+    /// Include the header name in the rejection messages, e.g. `Invalid X-Endpoint-API-UserInfo : Not found`, which is the default.
+    /// When disabled, the messages read generically, e.g. `Invalid user info : Not found`, so the gateway header conventions aren't leaked to the clients.
+    /// A custom message formatter, if any, takes precedence.
+    pub fn header_name_in_messages(mut self, enabled: bool) -> Self {
+        self.header_name_hidden = !enabled;
+        self
+    }
+
+    /// Set a trust boundary, a request being trusted when it holds the shared secret of the trusted proxy in the given header.
+    /// A trusted request keeps the whole user info, while an untrusted one, e.g. sent directly by a client in development, only keeps the allowlisted top-level claims.
+    /// The claims are filtered right after the parse, so the validations, e.g. `require_jti`, and the following hooks, e.g. the user lookup, only see the filtered user info.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().trust_boundary("X-Proxy-Secret", "my-secret", ["sub", "name"]);
+    /// ```
+    pub fn trust_boundary<I, K>(
+        mut self,
+        header: impl Into<String>,
+        secret: impl Into<String>,
+        allowed_claims: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.trust_boundary = Some(TrustBoundary {
+            header: header.into(),
+            secret: secret.into(),
+            allowed_claims: allowed_claims.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// Send an `ExtractionEvent`, with its outcome, subject and timestamp, on the given bounded channel for each extraction, e.g. for a custom audit pipeline.
+    /// The events are sent without waiting, so an event is dropped, and counted in `dropped_events`, when the channel is full.
+    /// A cached extraction doesn't send any event.
+    pub fn extraction_events(mut self, sender: Sender<ExtractionEvent>) -> Self {
+        self.events = Some(EventSender {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        });
+        self
+    }
+
+    /// Get the count of the extraction events dropped since the channel was configured, shared by all the clones of this configuration.
+    pub fn dropped_events(&self) -> u64 {
+        self.events
+            .as_ref()
+            .map_or(0, |events| events.dropped.load(Ordering::Relaxed))
+    }
+
+    /// Deserialize the typed user info, i.e. of `ExtractUserInfoAs`, leniently, coercing the numeric and boolean strings, e.g. `"1516239022"` or `"true"`, when a number or a boolean is expected.
+    /// It's disabled by default, so a stringified number or boolean is rejected as `serde_json` does.
+    pub fn lenient_types(mut self, enabled: bool) -> Self {
+        self.lenient_types = enabled;
+        self
+    }
+
+    /// Reject, with a 401 Unauthorized, a user info issued more than the given max age ago, according to its `iat` claim, whatever its `exp` claim, e.g. to force a re-authentication.
+    /// A missing or non numeric `iat` claim isn't rejected.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// When the `X-Endpoint-API-UserInfo` header is missing, look for it in the trailers of the request, as sent by some rare proxy setups.
+    /// As the trailers follow the body, it's only supported by `require_user_info_layer`, which buffers the whole body to read them, the extractors only seeing the headers.
+    /// The buffered body is then handed to the inner service without its trailers.
+    ///
+    /// Buffering holds the whole body in memory and delays the inner service until the last byte is received, i.e. no streaming of the requests lacking the header.
+    /// The buffered body is capped by `trailer_body_limit`, 2 MiB by default, a longer one being rejected with a 413 Payload Too Large.
+    pub fn trailer_fallback(mut self, enabled: bool) -> Self {
+        self.trailer_fallback = enabled;
+        self
+    }
+
+    /// Cap, in bytes, the body buffered by the `trailer_fallback`, rejecting a longer one with a 413 Payload Too Large instead of exhausting the memory, 2 MiB by default.
+    pub fn trailer_body_limit(mut self, max: usize) -> Self {
+        self.trailer_body_limit = Some(max);
+        self
+    }
+
+    /// Decode the header values longer than the threshold, in bytes, on the blocking pool, so a large payload doesn't stall the async runtime.
+    /// At most `max_concurrent` heavy decodes run at once, protecting the blocking pool under load, the following ones either waiting or being rejected with a 503 Service Unavailable.
+    pub fn heavy_decodes(
+        mut self,
+        threshold: usize,
+        max_concurrent: usize,
+        overflow: HeavyDecodeOverflow,
+    ) -> Self {
+        self.heavy_decodes = Some(HeavyDecodes {
+            threshold,
+            max_concurrent,
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            overflow,
+        });
+        self
+    }
+
+    /// Reproduce the rejection messages of the 0.2 release, so the clients parsing them keep working during an upgrade.
+    /// The rejections introduced since then are rendered as the closest 0.2 one when there's any, e.g. a truncated base 64 is `Not a valid base 64`, otherwise with their own message.
+    /// It takes precedence over `header_name_in_messages`, while a custom message formatter takes precedence over it.
+    ///
+    /// This compatibility shim is meant to be temporary, and will be removed in a future major release.
+    pub fn legacy_messages(mut self, enabled: bool) -> Self {
+        self.legacy_messages = enabled;
+        self
+    }
+
+    /// Split the `X-Endpoint-API-UserInfo` header on commas, as concatenated by a batching proxy, each segment being decoded as its own identity by `ExtractUserInfos`.
+    /// An invalid segment either rejects the whole request or is skipped, according to the handling.
+    /// It can't be combined with `hmac_signature`, whose single signature covers a single identity.
+    pub fn multi_identity(mut self, handling: MultiIdentityHandling) -> Self {
+        self.multi_identity = Some(handling);
+        self
+    }
+
+    /// Set the name of the path parameter identifying the resource owner, compared by `ResourceOwner` with the `sub` claim, e.g. `user_id` for `/users/{user_id}`.
+    /// It's `id` by default.
+    pub fn owner_path_param(mut self, name: &str) -> Self {
+        self.owner_path_param = Some(name.to_string());
+        self
+    }
+
+    /// Reject, with a 431 Request Header Fields Too Large, a header value longer than the given length, in bytes, before decoding it.
+    /// It bounds the allocations of a single extraction, the decoded bytes and the parsed claims being proportional to the header length.
+    pub fn max_header_len(mut self, max: usize) -> Self {
+        self.max_header_len = Some(max);
+        self
+    }
+
+    /// Map the given claim, e.g. `tenant`, as the `tid` claim of `ExtractUserInfo::tenant_id`, for the non Azure AD gateways.
+    /// When the `tid` claim is missing, the aliased claim is copied as `tid` once the transforms are applied.
+    pub fn tenant_id_alias(mut self, claim: &str) -> Self {
+        self.tenant_id_alias = Some(claim.to_string());
+        self
+    }
+
+    /// Reject, with a 400 Bad Request naming the first one, a top-level claim not being one of the allowed keys, for the strict API contracts.
+    /// Unlike the allowlist of `trust_boundary`, silently dropping the other claims of an untrusted request, it rejects the whole request.
+    pub fn allowed_keys<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.allowed_keys = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Compare the `nonce` claim with the `ExpectedNonce` of the request extensions, rejecting a mismatch or a missing `nonce` claim with a 400 Bad Request, e.g. for the OIDC flows.
+    /// A request without any `ExpectedNonce` extension is rejected with a 500 Internal Server Error, as its middleware is missing or misordered, so it must only be enabled on the routes concluding an OIDC flow.
+    pub fn nonce_check(mut self, enabled: bool) -> Self {
+        self.nonce_check = enabled;
+        self
+    }
+
+    /// Decode the base 64 header value as a stream, stopping at the given decoded length, in bytes, and rejecting a longer one with a 413 Payload Too Large.
+    /// Unlike `max_header_len`, checked on the header length, the decoded bytes are never allocated beyond the cap, the decoding being aborted mid-stream.
+    /// The other encodings, behind the `base32` and `base62` features, are decoded as usual.
+    pub fn max_decoded_len(mut self, max: usize) -> Self {
+        self.max_decoded_len = Some(max);
+        self
+    }
+
+    /// When the `X-Endpoint-API-UserInfo` header is missing, synthesize the claims of the mTLS client certificate from the `X-Forwarded-Client-Cert` (XFCC) header, e.g. for the service-to-service calls.
+    /// Only the last XFCC element is read, the `sub` claim being its URI SAN, e.g. a SPIFFE ID, or else the CN of its subject, or else its first DNS SAN, along with the `cert_uri`, `cert_subject`, `cert_dns` and `cert_hash` claims.
+    /// The synthesized claims skip the decoding stages, e.g. the claims pointer, but go through the trust boundary, the transforms and the claim validations as usual.
+    /// The certificate isn't verified, so the XFCC header must be sanitized by the proxy.
+    /// It's a separate trust source the `hmac_signature` doesn't cover, so with a signature the synthesized claims are rejected with a 401 Unauthorized, which `validate_config` reports.
+    pub fn xfcc_fallback(mut self, enabled: bool) -> Self {
+        self.xfcc_fallback = enabled;
+        self
+    }
+
+    /// Set the given header, e.g. `X-User-Hash`, to the hash of the `sub` claim, see `ExtractUserInfo::subject_hash`, on both the request and the response, e.g. to correlate the downstream calls of a user. Any client supplied value of this header is removed from the request, even when there is no `sub` to hash.
+    /// Only supported by `require_user_info_layer`, the request header being forwarded to the inner service, e.g. to propagate it to the downstream calls.
+    pub fn subject_hash_header(mut self, header: impl Into<String>) -> Self {
+        self.subject_hash_header = Some(header.into());
+        self
+    }
+
+    /// Guard the JSON parse, rejecting with a 400 Bad Request a decoded value nested deeper than `max_depth` or having more than `max_values` values, e.g. an adversarial small but deeply nested payload.
+    /// The guard is a linear scan of the decoded bytes, aborted at the first byte exceeding a limit, before any parse work, so the parse cost is bounded independently of the decoding one.
+    /// Without it, the nesting is still bounded by the 128 levels of `serde_json`.
+    pub fn parse_guard(mut self, max_depth: usize, max_values: usize) -> Self {
+        self.parse_guard = Some((max_depth, max_values));
+        self
+    }
+
+    /// Check the companion header, e.g. `X-Endpoint-API-UserInfo-Content-Type`, hinting the media type of the user info, rejecting any other than `application/json` with a 415 Unsupported Media Type.
+    /// The media type is matched case-insensitively, ignoring its parameters, e.g. `application/json; charset=utf-8`, and a request without the companion header is accepted.
+    pub fn content_type_header(mut self, header: impl Into<String>) -> Self {
+        self.content_type_header = Some(header.into());
+        self
+    }
+
+    /// Set the claims, e.g. `sub` and the tenant, of the claims context read via `context_claim` within the task serving the request, e.g. for the structured logs.
+    /// Only supported by `require_user_info_layer`, the context being set for the inner service and dropped once its response is returned.
+    pub fn context_claims<I, K>(mut self, claims: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.context_claims = Some(claims.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Decode the header value as a lenient base 64, composing the quirks of the proxies in any combination, i.e. the whitespaces being stripped, the URL-safe alphabet (RFC 4648) being accepted and the padding being optional.
+    /// It supersedes `mime_base64`, and contradicts `base64_length_check`, a value without its padding having any length.
+    pub fn lenient_base64(mut self, enabled: bool) -> Self {
+        self.lenient_base64 = enabled;
+        self
+    }
+
+    /// Require a `jti` claim, being a string, rejecting its absence with a 400 Bad Request, e.g. for a replay cache of the seen `jti` values, read via `ExtractUserInfo::jti`.
+    pub fn require_jti(mut self, enabled: bool) -> Self {
+        self.require_jti = enabled;
+        self
+    }
+
+    /// Strip a leading UTF-8 byte order mark (BOM) from the decoded bytes before parsing them, as prepended by some encoders.
+    /// By default, a BOM-prefixed JSON is rejected as not being a valid JSON.
+    pub fn strip_bom(mut self, enabled: bool) -> Self {
+        self.strip_bom = enabled;
+        self
+    }
+
+    /// Set the handling of an `aud` claim being a JSON object, either rejected or replaced by the array of its string values before the validations.
+    /// By default, the object is kept as is, and rejected with a distinct message by the `expected_audiences` check.
+    pub fn audience_object(mut self, handling: AudienceObjectHandling) -> Self {
+        self.audience_object = Some(handling);
+        self
+    }
+
+    /// Reject, with a 400 Bad Request naming the claim, an array claim among the given ones, e.g. `roles` or `scope`, having duplicate elements, as sent by a tampering or a buggy gateway.
+    pub fn unique_array_claims<I, K>(mut self, claims: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.unique_array_claims = Some(claims.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Verify a detached signature of the user info, i.e. the hexadecimal HMAC-SHA256 of its decoded bytes with the given key, sent by the trusted gateway in the given header.
+    /// The signature is verified over the decoded bytes exactly as sent, before any BOM stripping or parse, and the request is rejected with a 401 Unauthorized when it's missing or mismatching.
+    /// Every extractor decoding the header checks it, `LazyUserInfo` and `ExtractUserInfos` included, the latter rejecting each identity of a `multi_identity` header as no single signature covers them, which `validate_config` reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().hmac_signature("X-UserInfo-Signature", "my-key");
+    /// ```
+    pub fn hmac_signature(mut self, header: impl Into<String>, key: impl Into<Vec<u8>>) -> Self {
+        self.signature = Some(DetachedSignature {
+            header: header.into(),
+            key: key.into(),
+        });
+        self
+    }
+
+    /// Set the status code of the shape errors, i.e. a valid base 64 encoded and valid JSON user info not matching the expected schema, e.g. a `NotObject` or an `InvalidClaims` rejection, instead of a 400 Bad Request.
+    /// The transport errors, e.g. a `NotBase64` or a `NotJson` rejection, are still rejected with a 400 Bad Request, so `StatusCode::UNPROCESSABLE_ENTITY` tells them apart.
+    /// See `UserInfoRejection::is_shape_error` for the shape errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use axum::http::StatusCode;
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().shape_error_status(StatusCode::UNPROCESSABLE_ENTITY);
+    /// ```
+    pub fn shape_error_status(mut self, status: StatusCode) -> Self {
+        self.shape_error_status = Some(status);
+        self
+    }
+
+    /// Insert the `ExtractionOutcome` of `require_user_info_layer` into the response extensions, on the success path as on a rejection, e.g. for an access log layer running after the handler.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().outcome_extension(true);
+    /// ```
+    pub fn outcome_extension(mut self, enabled: bool) -> Self {
+        self.outcome_extension = enabled;
+        self
+    }
+
+    /// Reject, with a 401 Unauthorized, a user info whose `nbf` or `iat` claim, in seconds since the Unix epoch, is in the future, i.e. not yet valid.
+    /// A missing or non numeric `nbf` or `iat` claim isn't rejected.
+    pub fn reject_not_yet_valid(mut self, enabled: bool) -> Self {
+        self.reject_not_yet_valid = enabled;
+        self
+    }
+
+    /// Allow the given clock skew, between the issuer and this service, consistently in all the temporal checks, i.e. `reject_expired`, `reject_not_yet_valid` and `max_age`.
+    /// An `exp` claim is only expired, and an `iat` claim only older than the max age, the given skew later, while an `nbf` or an `iat` claim is only in the future beyond the given skew.
+    /// It's zero by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new()
+    ///     .reject_expired(true)
+    ///     .reject_not_yet_valid(true)
+    ///     .clock_skew(Duration::from_secs(30));
+    /// ```
+    pub fn clock_skew(mut self, skew: Duration) -> Self {
+        self.clock_skew = skew;
+        self
+    }
+
+    /// When the `X-Endpoint-API-UserInfo` header is missing, read the user info from the given query parameter, e.g. in a browser redirect of an OAuth flow, decoded and validated exactly as the header.
+    /// The query parameter is URL-decoded first, so a `+` of the standard base 64 alphabet must be percent-encoded as `%2B`, otherwise being a space, or the URL-safe alphabet used along with `lenient_base64`.
+    /// The header takes precedence when both are present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// let config = UserInfoConfig::new().query_param("user_info");
+    /// ```
+    pub fn query_param(mut self, name: impl Into<String>) -> Self {
+        self.query_param = Some(name.into());
+        self
+    }
+
+    /// Accept the given top-level JSON types only, e.g. an object or an array, rejecting any other one with a 400 Bad Request naming it, e.g. `JSON string not accepted`.
+    /// By default, any top-level type is accepted, `require_object` being the shortcut for an object only.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::{JsonType, UserInfoConfig};
+    ///
+    /// let config = UserInfoConfig::new().accepted_types([JsonType::Object, JsonType::Array]);
+    /// ```
+    pub fn accepted_types<I>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = JsonType>,
+    {
+        self.accepted_types = Some(types.into_iter().collect());
+        self
+    }
+
+    /// Log, at the given level, one consolidated line per extraction, e.g. for an audit record, with its outcome, its rejection reason, its subject, its decoded byte length and its duration as `key=value` fields, a missing one being `null`.
+    /// The subject is redacted when `sub` is one of the `sensitive_keys`, and a cached extraction doesn't log any line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoConfig;
+    ///
+    /// // [X-Endpoint-API-UserInfo] Extraction outcome="extracted" reason=null subject="my-subject" decoded_len=170 duration_us=42
+    /// let config = UserInfoConfig::new().audit_log(log::Level::Info);
+    /// ```
+    pub fn audit_log(mut self, level: log::Level) -> Self {
+        self.audit_log = Some(level);
+        self
+    }
+
+    /// Check whether the claim is sensitive, according to the configured denylist or the default one.
+    pub(crate) fn is_sensitive(&self, key: &str) -> bool {
+        match &self.sensitive_keys {
+            Some(sensitive_keys) => sensitive_keys.contains(key),
+            None => DEFAULT_SENSITIVE_KEYS.contains(&key),
+        }
+    }
+
+    /// Get the effective handling of a top-level empty JSON array.
+    pub(crate) fn empty_array_handling(&self) -> EmptyArrayHandling {
+        match self.empty_array {
+            Some(handling) => handling,
+            None if self.require_object => EmptyArrayHandling::Reject,
+            None => EmptyArrayHandling::Accept,
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    ExtractUserInfoRejection, UserInfoConfig, UserInfoRejection, decode::decode_json,
+    extract::extract,
+};
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderValue, request::Parts},
+};
+use serde_json::Value;
+
+/// This is a custom extractor for Axum that extracts the consumer info, via the `X-Endpoint-API-Consumer` header forwarded by ESPv2 alongside the user info.
+/// The consumer info identifies the API consumer, e.g. from its API key, by its project number and id.
+/// If the `X-Endpoint-API-Consumer` header is present, it's decoded as a base 64 encoded JSON value, exactly as the `X-Endpoint-API-UserInfo` header is.
+/// If the `X-Endpoint-API-Consumer` header is invalid, it returns a 400 Bad Request error with a specific message.
+/// If the `X-Endpoint-API-Consumer` header is not present, it returns `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractConsumerInfo;
+///
+/// async fn handler(consumer_info: ExtractConsumerInfo) {
+///     println!("Consumer project : {:?}", consumer_info.project_id());
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExtractConsumerInfo(pub Option<Value>);
+
+pub(crate) const HEADER_X_CONSUMER: &str = "X-Endpoint-API-Consumer";
+
+impl ExtractConsumerInfo {
+    /// Get the project number of the consumer, either as a string or a number in the consumer info.
+    pub fn project_number(&self) -> Option<String> {
+        match self.0.as_ref()?.get("project_number")? {
+            Value::String(project_number) => Some(project_number.clone()),
+            Value::Number(project_number) => Some(project_number.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Get the project id of the consumer.
+    pub fn project_id(&self) -> Option<&str> {
+        self.0.as_ref()?.get("project_id")?.as_str()
+    }
+}
+
+impl<S> FromRequestParts<S> for ExtractConsumerInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<UserInfoConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        parse_consumer(parts.headers.get(HEADER_X_CONSUMER), &config)
+            .map(ExtractConsumerInfo)
+            .map_err(|rejection| ExtractUserInfoRejection::new(rejection, &config))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts both the user info and the consumer info, via the `X-Endpoint-API-UserInfo` and `X-Endpoint-API-Consumer` headers.
+/// Each header is decoded independently, with its own rejection, so the user info is required while the consumer info is `None` when its header is missing.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserAndConsumerInfo;
+///
+/// async fn handler(ExtractUserAndConsumerInfo { user, consumer }: ExtractUserAndConsumerInfo) {
+///     println!("User Info: {:?} (consumer: {:?})", user, consumer);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserAndConsumerInfo {
+    /// The user info, from the `X-Endpoint-API-UserInfo` header.
+    pub user: Value,
+    /// The consumer info, from the `X-Endpoint-API-Consumer` header, if any.
+    pub consumer: Option<Value>,
+}
+
+impl<S> FromRequestParts<S> for ExtractUserAndConsumerInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+        let ExtractConsumerInfo(consumer) =
+            ExtractConsumerInfo::from_request_parts(parts, state).await?;
+
+        Ok(ExtractUserAndConsumerInfo {
+            user: extraction.value,
+            consumer,
+        })
+    }
+}
+
+/// Decode and parse the optional `X-Endpoint-API-Consumer` header value.
+pub(crate) fn parse_consumer(
+    consumer_info: Option<&HeaderValue>,
+    config: &UserInfoConfig,
+) -> Result<Option<Value>, UserInfoRejection> {
+    let Some(consumer_info) = consumer_info else {
+        return Ok(None);
+    };
+
+    match decode_json(consumer_info, HEADER_X_CONSUMER, config) {
+        Ok((consumer_info, _)) => Ok(Some(consumer_info)),
+        Err(_) => Err(UserInfoRejection::InvalidConsumer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractConsumerInfo, ExtractUserAndConsumerInfo, UserInfoRejection, fixtures};
+    use axum::{
+        body::Body,
+        extract::FromRequestParts,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_consumer_extract_consumer_info_with_header() {
+        let consumer_info = json!({ "project_number": 123456789, "project_id": "my-project" });
+        let request = Request::builder()
+            .header("X-Endpoint-API-Consumer", fixtures::encode(&consumer_info))
+            .body(Body::empty())
+            .unwrap();
+
+        let mut parts = request.into_parts().0;
+
+        let consumer_info = ExtractConsumerInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(consumer_info.project_number().as_deref(), Some("123456789"));
+        assert_eq!(consumer_info.project_id(), Some("my-project"));
+    }
+
+    #[tokio::test]
+    async fn test_consumer_extract_consumer_info_with_header_ko_not_base64() {
+        let request = Request::builder()
+            .header("X-Endpoint-API-Consumer", "this-is-not-a-base64")
+            .body(Body::empty())
+            .unwrap();
+
+        let mut parts = request.into_parts().0;
+
+        let consumer_info = ExtractConsumerInfo::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            consumer_info.unwrap_err(),
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid X-Endpoint-API-Consumer : Not a valid base 64 encoded JSON".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consumer_extract_consumer_info_without_header() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let consumer_info = ExtractConsumerInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(consumer_info.0, None);
+        assert_eq!(consumer_info.project_number(), None);
+        assert_eq!(consumer_info.project_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_extract_user_and_consumer_info_both() {
+        let consumer_info = json!({ "project_number": "123456789", "project_id": "my-project" });
+        let request = Request::builder()
+            .header("X-Endpoint-API-UserInfo", fixtures::USER_INFO)
+            .header("X-Endpoint-API-Consumer", fixtures::encode(&consumer_info))
+            .body(Body::empty())
+            .unwrap();
+
+        let mut parts = request.into_parts().0;
+
+        let user_and_consumer_info =
+            ExtractUserAndConsumerInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_and_consumer_info.user, fixtures::user_info());
+        assert_eq!(user_and_consumer_info.consumer, Some(consumer_info));
+    }
+
+    #[tokio::test]
+    async fn test_consumer_extract_user_and_consumer_info_user_only() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let user_and_consumer_info =
+            ExtractUserAndConsumerInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_and_consumer_info.user, fixtures::user_info());
+        assert_eq!(user_and_consumer_info.consumer, None);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_extract_user_and_consumer_info_neither() {
+        let mut parts = fixtures::parts(None, None);
+
+        let user_and_consumer_info =
+            ExtractUserAndConsumerInfo::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            user_and_consumer_info.unwrap_err(),
+            UserInfoRejection::NotFound
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use serde_json::{Map, Value};
+
+tokio::task_local! {
+    /// This is the claims context of the current request, set by `require_user_info_layer` for the configured claims.
+    pub(crate) static CONTEXT: Map<String, Value>;
+}
+
+/// Get a claim from the claims context of the current request, e.g. to add the `sub` or the tenant to the structured logs without threading the user info through the calls.
+/// The context is only set by `require_user_info_layer`, for the claims configured via `UserInfoConfig::context_claims`, and only within the task serving the request, so it returns `None` outside of it, e.g. in a spawned task, or for a missing claim.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::{context_claim, require_user_info_layer, UserInfoConfig};
+///
+/// fn audit(action: &str) {
+///     println!("{} by {:?}", action, context_claim("sub"));
+/// }
+///
+/// async fn handler() {
+///     audit("Read");
+/// }
+///
+/// let app = Router::<()>::new()
+///     .route("/foo", get(handler))
+///     .route_layer(require_user_info_layer(UserInfoConfig::new().context_claims(["sub", "tenant_id"])));
+/// ```
+pub fn context_claim(claim: &str) -> Option<Value> {
+    CONTEXT
+        .try_with(|context| context.get(claim).cloned())
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UserInfoConfig, context_claim, fixtures, require_user_info_layer};
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_context_context_claim_during_handler() {
+        async fn handler() -> String {
+            tokio::task::yield_now().await;
+
+            format!(
+                "{:?} {:?} {:?}",
+                context_claim("sub"),
+                context_claim("tenant_id"),
+                context_claim("name")
+            )
+        }
+
+        let config = UserInfoConfig::new().context_claims(["sub", "tenant_id"]);
+        let app = Router::new()
+            .route("/foo", get(handler))
+            .route_layer(require_user_info_layer(config));
+        let user_info = fixtures::encode(&json!({
+            "sub": "my-subject",
+            "tenant_id": "my-tenant",
+            "name": "my-name"
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/foo")
+                    .header("X-Endpoint-API-UserInfo", user_info)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.into_body().collect().await.unwrap().to_bytes(),
+            r#"Some(String("my-subject")) Some(String("my-tenant")) None"#
+        );
+        assert_eq!(context_claim("sub"), None);
+    }
+}
@@ -0,0 +1,651 @@
+use crate::{HeaderEncoding, UserInfoConfig, UserInfoRejection, logging::log_error};
+use axum::http::HeaderValue;
+use base64::{
+    DecodeError, Engine, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig, general_purpose},
+    read::DecoderReader,
+};
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
+
+/// This is the UTF-8 byte order mark.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// This is the engine of the lenient base 64, with the standard alphabet and an optional padding.
+const LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Normalize the base 64 header value, according to the configuration, returning it along with the engine decoding it.
+/// In MIME mode (RFC 2045), the line breaks and other whitespaces inserted every 76 characters are stripped.
+/// In lenient mode, the whitespaces are stripped too, then the URL-safe alphabet is mapped onto the standard one, the engine accepting a missing padding, so the quirks compose in any combination.
+fn normalize_base64<'a>(
+    value: &'a str,
+    config: &UserInfoConfig,
+) -> (Cow<'a, str>, &'static GeneralPurpose) {
+    if config.lenient_base64 {
+        let value = value
+            .chars()
+            .filter(|character| !character.is_ascii_whitespace())
+            .map(|character| match character {
+                '-' => '+',
+                '_' => '/',
+                _ => character,
+            })
+            .collect();
+
+        return (Cow::Owned(value), &LENIENT);
+    }
+
+    if config.mime_base64 {
+        let value = value
+            .chars()
+            .filter(|character| !character.is_ascii_whitespace())
+            .collect();
+
+        return (Cow::Owned(value), &general_purpose::STANDARD);
+    }
+
+    (Cow::Borrowed(value), &general_purpose::STANDARD)
+}
+
+/// Decode the base 64 header value, according to the configuration.
+pub(crate) fn decode_base64(value: &str, config: &UserInfoConfig) -> Result<Vec<u8>, DecodeError> {
+    let (value, engine) = normalize_base64(value, config);
+
+    engine.decode(value.as_bytes())
+}
+
+/// Decode the base 64 header value as a stream, reading at most one byte over the max decoded length, so an oversized value is never fully allocated.
+/// It returns `None` when the decoded value is longer than the max decoded length.
+fn decode_base64_bounded(
+    value: &str,
+    max: usize,
+    config: &UserInfoConfig,
+) -> io::Result<Option<Vec<u8>>> {
+    let (value, engine) = normalize_base64(value, config);
+
+    let mut decoded = Vec::new();
+    DecoderReader::new(value.as_bytes(), engine)
+        .take(max as u64 + 1)
+        .read_to_end(&mut decoded)?;
+
+    Ok((decoded.len() <= max).then_some(decoded))
+}
+
+/// Decode the base 32 (RFC 4648) header value, case-insensitively and with an optional padding.
+#[cfg(feature = "base32")]
+pub(crate) fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim_end_matches('=');
+
+    if matches!(value.len() % 8, 1 | 3 | 6) {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(value.len() * 5 / 8);
+    let (mut bits, mut len) = (0u16, 0u8);
+
+    for character in value.bytes() {
+        let digit = match character.to_ascii_uppercase() {
+            character @ b'A'..=b'Z' => character - b'A',
+            character @ b'2'..=b'7' => character - b'2' + 26,
+            _ => return None,
+        };
+
+        bits = (bits << 5) | u16::from(digit);
+        len += 5;
+
+        if len >= 8 {
+            len -= 8;
+            decoded.push((bits >> len) as u8);
+            bits &= (1 << len) - 1;
+        }
+    }
+
+    (bits == 0).then_some(decoded)
+}
+
+/// Decode the base 62 header value, i.e. a big-endian number written with the `0-9A-Za-z` digits, each leading `0` digit being a leading zero byte.
+/// Each digit is multiplied into the whole number decoded so far, so the cost is quadratic in the header length, hence the `max_header_len` required by `validate_config`.
+#[cfg(feature = "base62")]
+pub(crate) fn decode_base62(value: &str) -> Option<Vec<u8>> {
+    let zeros = value
+        .bytes()
+        .take_while(|&character| character == b'0')
+        .count();
+    let mut number: Vec<u8> = Vec::with_capacity(value.len() * 3 / 4);
+
+    for character in value.bytes().skip(zeros) {
+        let mut carry = u32::from(match character {
+            b'0'..=b'9' => character - b'0',
+            b'A'..=b'Z' => character - b'A' + 10,
+            b'a'..=b'z' => character - b'a' + 36,
+            _ => return None,
+        });
+
+        for byte in number.iter_mut().rev() {
+            carry += u32::from(*byte) * 62;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            number.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut decoded = vec![0; zeros];
+    decoded.append(&mut number);
+
+    Some(decoded)
+}
+
+/// Log and build the rejection of a header value not being valid in the configured encoding.
+#[cfg(any(feature = "base32", feature = "base62"))]
+fn invalid_encoding(
+    header: &str,
+    encoding: HeaderEncoding,
+    config: &UserInfoConfig,
+) -> UserInfoRejection {
+    log_error!(config, "[{}] Failed to decode {}", header, encoding.name());
+
+    UserInfoRejection::InvalidEncoding { encoding }
+}
+
+/// Decode the header value, according to the configured encoding, returning the decoded bytes to be parsed.
+pub(crate) fn decode_bytes(
+    value: &HeaderValue,
+    header: &str,
+    config: &UserInfoConfig,
+) -> Result<Vec<u8>, UserInfoRejection> {
+    if let Some(max) = config.max_header_len
+        && value.len() > max
+    {
+        log_error!(
+            config,
+            "[{}] Rejected a header length of {} exceeding {}",
+            header,
+            value.len(),
+            max
+        );
+
+        return Err(UserInfoRejection::TooLarge {
+            len: value.len(),
+            max,
+        });
+    }
+
+    let value = match value.to_str() {
+        Ok(value) => value.trim(),
+        Err(err) => {
+            log_error!(
+                config,
+                "[{}] Failed to read header due to : {:?}",
+                header,
+                err
+            );
+
+            return Err(UserInfoRejection::NotBase64);
+        }
+    };
+
+    if config.base64_length_check && config.encoding == HeaderEncoding::Base64 {
+        let len = if config.mime_base64 {
+            value
+                .chars()
+                .filter(|character| !character.is_ascii_whitespace())
+                .count()
+        } else {
+            value.len()
+        };
+
+        if len % 4 != 0 {
+            log_error!(config, "[{}] Rejected a base 64 length of {}", header, len);
+
+            return Err(UserInfoRejection::InvalidBase64Length { len });
+        }
+    }
+
+    if config.encoding == HeaderEncoding::Base64
+        && let Some(max) = config.max_decoded_len
+    {
+        return match decode_base64_bounded(value, max, config) {
+            Ok(Some(decoded)) => Ok(decoded),
+            Ok(None) => {
+                log_error!(
+                    config,
+                    "[{}] Aborted a base 64 decoding exceeding {} bytes",
+                    header,
+                    max
+                );
+
+                Err(UserInfoRejection::PayloadTooLarge { max })
+            }
+            Err(err) => {
+                log_error!(
+                    config,
+                    "[{}] Failed to decode base 64 due to : {:?}",
+                    header,
+                    err
+                );
+
+                Err(UserInfoRejection::NotBase64)
+            }
+        };
+    }
+
+    match config.encoding {
+        HeaderEncoding::Base64 => match decode_base64(value, config) {
+            Ok(decoded) => Ok(decoded),
+            Err(err) => {
+                log_error!(
+                    config,
+                    "[{}] Failed to decode base 64 due to : {:?}",
+                    header,
+                    err
+                );
+
+                Err(UserInfoRejection::NotBase64)
+            }
+        },
+        #[cfg(feature = "base32")]
+        HeaderEncoding::Base32 => decode_base32(value)
+            .ok_or_else(|| invalid_encoding(header, HeaderEncoding::Base32, config)),
+        #[cfg(feature = "base62")]
+        HeaderEncoding::Base62 => decode_base62(value)
+            .ok_or_else(|| invalid_encoding(header, HeaderEncoding::Base62, config)),
+    }
+}
+
+/// Decode and parse a base 64 encoded JSON header value, returning the JSON value along with its decoded byte length.
+/// It's shared by the headers forwarded by the gateway, so they all get the same pipeline, the header name only being used for logging.
+pub(crate) fn decode_json(
+    value: &HeaderValue,
+    header: &str,
+    config: &UserInfoConfig,
+) -> Result<(Value, usize), UserInfoRejection> {
+    let decoded = decode_bytes(value, header, config)?;
+
+    parse_json(&decoded, header, config).map(|parsed| (parsed, decoded.len()))
+}
+
+/// Parse the decoded bytes of a header value as JSON, stripping a leading BOM when configured and within the parse guard if any.
+pub(crate) fn parse_json(
+    decoded: &[u8],
+    header: &str,
+    config: &UserInfoConfig,
+) -> Result<Value, UserInfoRejection> {
+    let json = match decoded.strip_prefix(BOM) {
+        Some(json) if config.strip_bom => json,
+        _ => decoded,
+    };
+
+    if let Some((max_depth, max_values)) = config.parse_guard
+        && let Some(offset) = exceeding_offset(json, max_depth, max_values)
+    {
+        log_error!(
+            config,
+            "[{}] Aborted the parse at byte {} exceeding a depth of {} or {} values",
+            header,
+            offset,
+            max_depth,
+            max_values
+        );
+
+        return Err(UserInfoRejection::ParseAborted { offset });
+    }
+
+    match serde_json::from_slice(json) {
+        Ok(parsed) => Ok(parsed),
+        Err(err) => {
+            log_error!(
+                config,
+                "[{}] Failed to parse JSON due to : {:?}",
+                header,
+                err
+            );
+
+            Err(UserInfoRejection::NotJson {
+                line: err.line(),
+                column: err.column(),
+            })
+        }
+    }
+}
+
+/// Scan the JSON bytes for the first byte exceeding either the max depth or the max values, skipping the strings.
+/// The values, i.e. the root one and the elements or claims of the arrays and objects, are counted by the container openings and the separators, so a malformed JSON is only bounded, its errors being left to the parse.
+fn exceeding_offset(json: &[u8], max_depth: usize, max_values: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut values = 1;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, byte) in json.iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                values += 1;
+            }
+            b']' | b'}' => depth -= usize::from(depth > 0),
+            b',' => values += 1,
+            _ => {}
+        }
+
+        if depth > max_depth || values > max_values {
+            return Some(offset);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "base32", feature = "base62"))]
+    use crate::HeaderEncoding;
+    #[cfg(feature = "base32")]
+    use crate::decode::decode_base32;
+    #[cfg(feature = "base62")]
+    use crate::decode::decode_base62;
+    use crate::{
+        ExtractUserInfo, UserInfoConfig, UserInfoRejection, decode::decode_base64, fixtures,
+    };
+    use axum::{extract::FromRequestParts, http::StatusCode};
+    use base64::{Engine, engine::general_purpose};
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_base64_mime() {
+        let config = UserInfoConfig::new().mime_base64(true);
+        let value = format!(
+            "{}\r\n{}",
+            &fixtures::USER_INFO[..76],
+            &fixtures::USER_INFO[76..]
+        );
+
+        let decoded = decode_base64(&value, &config).unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(),
+            fixtures::user_info()
+        );
+    }
+
+    #[test]
+    fn test_decode_base64_mime_disabled() {
+        let value = format!(
+            "{}\r\n{}",
+            &fixtures::USER_INFO[..76],
+            &fixtures::USER_INFO[76..]
+        );
+
+        assert!(decode_base64(&value, &UserInfoConfig::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_not_json_position() {
+        let user_info = "{\n  \"sub\": \"my-subject\",\n  \"name\" \"my-name\"\n}";
+        let mut parts = fixtures::parts(Some(&general_purpose::STANDARD.encode(user_info)), None);
+
+        let rejection = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            rejection,
+            UserInfoRejection::NotJson {
+                line: 3,
+                column: 10
+            }
+        );
+        assert_eq!(
+            rejection.message(),
+            "Invalid X-Endpoint-API-UserInfo : Not a valid JSON at line 3 column 10"
+        );
+    }
+
+    #[cfg(feature = "base32")]
+    #[tokio::test]
+    async fn test_decode_json_base32() {
+        let config = UserInfoConfig::new().encoding(HeaderEncoding::Base32);
+        let mut parts = fixtures::parts(
+            Some("PMRHG5LCEI5CE3LZFVZXKYTKMVRXIIRMEJXGC3LFEI5CE3LZFVXGC3LFEJ6Q===="),
+            Some(config),
+        );
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            user_info,
+            serde_json::json!({ "sub": "my-subject", "name": "my-name" })
+        );
+    }
+
+    #[cfg(feature = "base32")]
+    #[tokio::test]
+    async fn test_decode_json_base32_invalid() {
+        let config = UserInfoConfig::new().encoding(HeaderEncoding::Base32);
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(config));
+
+        let rejection = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            rejection.message(),
+            "Invalid X-Endpoint-API-UserInfo : Not a valid base 32"
+        );
+    }
+
+    #[cfg(feature = "base32")]
+    #[test]
+    fn test_decode_base32_unpadded_lowercase() {
+        assert_eq!(decode_base32("mzxw6ytboi").unwrap(), b"foobar");
+        assert_eq!(decode_base32("MZXW6YTBOI======").unwrap(), b"foobar");
+        assert_eq!(decode_base32("MZXW6YQ").unwrap(), b"foob");
+        assert_eq!(decode_base32("MZXW6YR"), None);
+        assert_eq!(decode_base32("MZXW6Y"), None);
+    }
+
+    #[cfg(feature = "base62")]
+    #[tokio::test]
+    async fn test_decode_json_base62() {
+        let config = UserInfoConfig::new().encoding(HeaderEncoding::Base62);
+        let mut parts = fixtures::parts(
+            Some("97DePymMN40413uXaJLbwV7cI0uhEBeoNVD9HXmNAvKa62Ls7x"),
+            Some(config),
+        );
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            user_info,
+            serde_json::json!({ "sub": "my-subject", "name": "my-name" })
+        );
+    }
+
+    #[cfg(feature = "base62")]
+    #[test]
+    fn test_decode_base62_leading_zeros() {
+        assert_eq!(decode_base62("00").unwrap(), vec![0, 0]);
+        assert_eq!(decode_base62("0G8").unwrap(), vec![0, 3, 232]);
+        assert_eq!(decode_base62("not-base62"), None);
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_max_header_len() {
+        let under = UserInfoConfig::new().max_header_len(fixtures::USER_INFO.len());
+        let over = UserInfoConfig::new().max_header_len(fixtures::USER_INFO.len() - 1);
+        let mut under_parts = fixtures::parts(Some(fixtures::USER_INFO), Some(under));
+        let mut over_parts = fixtures::parts(Some(fixtures::USER_INFO), Some(over));
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut under_parts, &())
+            .await
+            .unwrap();
+        let rejection = ExtractUserInfo::from_request_parts(&mut over_parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(user_info, fixtures::user_info());
+        assert_eq!(
+            rejection.status(),
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+        assert_eq!(
+            rejection,
+            UserInfoRejection::TooLarge {
+                len: fixtures::USER_INFO.len(),
+                max: fixtures::USER_INFO.len() - 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_max_decoded_len() {
+        let config = UserInfoConfig::new().max_decoded_len(fixtures::user_info().to_string().len());
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(config));
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info, fixtures::user_info());
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_max_decoded_len_aborted_early() {
+        // A valid base 64 prefix, way over the cap, followed by an invalid base 64 tail never reached.
+        let user_info = format!("{}!!!!", "QUFB".repeat(4096));
+        let config = UserInfoConfig::new().max_decoded_len(16);
+        let mut parts = fixtures::parts(Some(&user_info), Some(config));
+
+        let rejection = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(rejection.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(rejection, UserInfoRejection::PayloadTooLarge { max: 16 });
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_parse_guard_within() {
+        let config = UserInfoConfig::new().parse_guard(2, 16);
+        let user_info =
+            fixtures::encode(&json!({ "sub": "my-subject", "groups": ["[admin]", "{users}"] }));
+        let mut parts = fixtures::parts(Some(&user_info), Some(config));
+
+        let user_info = ExtractUserInfo::from_request_parts(&mut parts, &()).await;
+
+        assert!(user_info.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_parse_guard_tripped() {
+        // A small but deeply nested payload, and a flat but wide one.
+        let deep =
+            general_purpose::STANDARD.encode(format!("{}{}", "[".repeat(64), "]".repeat(64)));
+        let wide = fixtures::encode(&json!(vec![0; 64]));
+        let config = UserInfoConfig::new().parse_guard(16, 32);
+        let mut deep_parts = fixtures::parts(Some(&deep), Some(config.clone()));
+        let mut wide_parts = fixtures::parts(Some(&wide), Some(config));
+
+        let deep = ExtractUserInfo::from_request_parts(&mut deep_parts, &())
+            .await
+            .unwrap_err();
+        let wide = ExtractUserInfo::from_request_parts(&mut wide_parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(deep.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(deep, UserInfoRejection::ParseAborted { offset: 16 });
+        assert_eq!(wide, UserInfoRejection::ParseAborted { offset: 62 });
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_lenient_base64_all_quirks() {
+        // The URL-safe, unpadded and folded base 64 of `{"sub":"my-subject","name":"??>>~~~"}`.
+        let user_info = "eyJzdWIiOiJteS1zdWJq ZWN0IiwibmFtZSI6Ij8_\tPj5-fn4ifQ";
+        let mut parts = fixtures::parts(
+            Some(user_info),
+            Some(UserInfoConfig::new().lenient_base64(true)),
+        );
+        let mut strict_parts = fixtures::parts(
+            Some(user_info),
+            Some(UserInfoConfig::new().mime_base64(true)),
+        );
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        let strict = ExtractUserInfo::from_request_parts(&mut strict_parts, &()).await;
+
+        assert_eq!(user_info, json!({ "sub": "my-subject", "name": "??>>~~~" }));
+        assert_eq!(strict.unwrap_err(), UserInfoRejection::NotBase64);
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_lenient_base64_bounded() {
+        let config = UserInfoConfig::new()
+            .lenient_base64(true)
+            .max_decoded_len(64);
+        let user_info = fixtures::USER_INFO.trim_end_matches('=').replace('/', "_");
+        let mut parts = fixtures::parts(Some(&user_info[..]), Some(config));
+
+        let user_info = ExtractUserInfo::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            user_info.unwrap_err(),
+            UserInfoRejection::PayloadTooLarge { max: 64 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_strip_bom() {
+        let user_info = general_purpose::STANDARD.encode(b"\xEF\xBB\xBF{\"sub\":\"my-subject\"}");
+        let mut parts = fixtures::parts(
+            Some(&user_info),
+            Some(UserInfoConfig::new().strip_bom(true)),
+        );
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info, json!({ "sub": "my-subject" }));
+    }
+
+    #[tokio::test]
+    async fn test_decode_json_strip_bom_disabled() {
+        let user_info = general_purpose::STANDARD.encode(b"\xEF\xBB\xBF{\"sub\":\"my-subject\"}");
+        let mut parts = fixtures::parts(Some(&user_info), None);
+
+        let user_info = ExtractUserInfo::from_request_parts(&mut parts, &()).await;
+
+        assert!(matches!(
+            user_info.unwrap_err().rejection(),
+            UserInfoRejection::NotJson { line: 1, .. }
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{UserInfoConfig, UserInfoRejection, extract::parse};
+use axum::http::{HeaderMap, HeaderValue};
+use base64::{
+    Engine, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use serde_json::Value;
+
+/// This is the base 64 alphabet (RFC 4648) decoding a header value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, with `+` and `/`.
+    Standard,
+    /// The URL-safe alphabet, with `-` and `_`.
+    UrlSafe,
+}
+
+/// This is the outcome of each stage of `decode_and_parse`, e.g. for a diagnostics endpoint explaining why a header value failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeReport {
+    /// Whether leading or trailing whitespaces were trimmed.
+    pub trimmed: bool,
+    /// The first alphabet decoding the value, whatever the configuration, its inner whitespaces being stripped and its padding being optional, or `None` when neither does.
+    pub alphabet: Option<Base64Alphabet>,
+    /// The decoded byte length, with the first alphabet decoding the value, if any.
+    pub decoded_len: Option<usize>,
+    /// Whether the decoded bytes are a valid JSON, if any.
+    pub json: Option<bool>,
+    /// The outcome of the extraction according to the configuration, i.e. the claims or the rejection `ExtractUserInfo` would return.
+    pub result: Result<Value, UserInfoRejection>,
+}
+
+/// Decode and parse the `X-Endpoint-API-UserInfo` header value, reporting the outcome of each stage, e.g. for a "why did my user info fail?" tool.
+/// The decoding stages try every base 64 alphabet, whatever the configuration, so a value only failing due to the configuration, e.g. a URL-safe one without `lenient_base64`, is explained, while the result is the one of the configured extraction.
+/// As it isn't bound to any request, the stages needing one, e.g. the `hmac_signature`, the `trust_boundary`, the `bearer_subject_check` or the `user_lookup`, are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use user_info_middleware::{Base64Alphabet, UserInfoConfig, UserInfoRejection, decode_and_parse};
+///
+/// // The URL-safe base 64 of `{"name":"??>>"}`.
+/// let report = decode_and_parse("eyJuYW1lIjoiPz8-PiJ9", &UserInfoConfig::new());
+///
+/// assert_eq!(report.alphabet, Some(Base64Alphabet::UrlSafe));
+/// assert_eq!(report.result, Err(UserInfoRejection::NotBase64));
+/// ```
+pub fn decode_and_parse(value: &str, config: &UserInfoConfig) -> DecodeReport {
+    let trimmed = value.trim();
+    let stripped: String = trimmed
+        .chars()
+        .filter(|character| !character.is_ascii_whitespace())
+        .collect();
+
+    let decoded = [
+        (Base64Alphabet::Standard, &alphabet::STANDARD),
+        (Base64Alphabet::UrlSafe, &alphabet::URL_SAFE),
+    ]
+    .into_iter()
+    .find_map(|(name, alphabet)| {
+        let engine = GeneralPurpose::new(
+            alphabet,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+
+        engine.decode(&stripped).ok().map(|decoded| (name, decoded))
+    });
+
+    // Without any request headers, the signature would always mismatch and the trust boundary would always filter.
+    let config = UserInfoConfig {
+        signature: None,
+        trust_boundary: None,
+        ..config.clone()
+    };
+
+    let result = match HeaderValue::from_str(value) {
+        Ok(header) => {
+            parse(Some(&header), &HeaderMap::new(), &config).map(|extraction| extraction.value)
+        }
+        Err(_) => Err(UserInfoRejection::NotBase64),
+    };
+
+    DecodeReport {
+        trimmed: trimmed.len() != value.len(),
+        alphabet: decoded.as_ref().map(|(alphabet, _)| *alphabet),
+        decoded_len: decoded.as_ref().map(|(_, decoded)| decoded.len()),
+        json: decoded
+            .as_ref()
+            .map(|(_, decoded)| serde_json::from_slice::<Value>(decoded).is_ok()),
+        result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Base64Alphabet, UserInfoConfig, UserInfoRejection, decode_and_parse, fixtures};
+
+    #[test]
+    fn test_diagnose_decode_and_parse_success() {
+        let report = decode_and_parse(
+            &format!(" {} ", fixtures::USER_INFO),
+            &UserInfoConfig::new(),
+        );
+
+        assert!(report.trimmed);
+        assert_eq!(report.alphabet, Some(Base64Alphabet::Standard));
+        assert_eq!(
+            report.decoded_len,
+            Some(fixtures::user_info().to_string().len())
+        );
+        assert_eq!(report.json, Some(true));
+        assert_eq!(report.result, Ok(fixtures::user_info()));
+    }
+
+    #[test]
+    fn test_diagnose_decode_and_parse_not_base64() {
+        let report = decode_and_parse("not base 64 !", &UserInfoConfig::new());
+
+        assert!(!report.trimmed);
+        assert_eq!(report.alphabet, None);
+        assert_eq!(report.decoded_len, None);
+        assert_eq!(report.json, None);
+        assert_eq!(report.result, Err(UserInfoRejection::NotBase64));
+    }
+
+    #[test]
+    fn test_diagnose_decode_and_parse_url_safe() {
+        // The URL-safe base 64 of `{"name":"??>>"}`.
+        let value = "eyJuYW1lIjoiPz8-PiJ9";
+
+        let strict = decode_and_parse(value, &UserInfoConfig::new());
+        let lenient = decode_and_parse(value, &UserInfoConfig::new().lenient_base64(true));
+
+        assert_eq!(strict.alphabet, Some(Base64Alphabet::UrlSafe));
+        assert_eq!(strict.json, Some(true));
+        assert_eq!(strict.result, Err(UserInfoRejection::NotBase64));
+        assert_eq!(lenient.result, Ok(serde_json::json!({ "name": "??>>" })));
+    }
+
+    #[test]
+    fn test_diagnose_decode_and_parse_not_json() {
+        // The base 64 of `{"sub":`.
+        let report = decode_and_parse("eyJzdWIiOg==", &UserInfoConfig::new());
+
+        assert_eq!(report.alphabet, Some(Base64Alphabet::Standard));
+        assert_eq!(report.decoded_len, Some(7));
+        assert_eq!(report.json, Some(false));
+        assert_eq!(
+            report.result,
+            Err(UserInfoRejection::NotJson { line: 1, column: 7 })
+        );
+    }
+
+    #[test]
+    fn test_diagnose_decode_and_parse_not_validated() {
+        // The base 64 of `[1]`.
+        let report = decode_and_parse("WzFd", &UserInfoConfig::new().require_object(true));
+
+        assert_eq!(report.json, Some(true));
+        assert_eq!(report.result, Err(UserInfoRejection::NotObject));
+    }
+}
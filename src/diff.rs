@@ -0,0 +1,98 @@
+use crate::ExtractUserInfo;
+use serde_json::Map;
+use std::collections::BTreeSet;
+
+/// This is the difference between two user infos, by top-level claim, e.g. to debug a transform pipeline.
+/// A user info that isn't a JSON object is compared as having no claims.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClaimDiff {
+    /// The claims only present in the other user info.
+    pub added: BTreeSet<String>,
+    /// The claims only present in this user info.
+    pub removed: BTreeSet<String>,
+    /// The claims present in both user infos, with different values.
+    pub changed: BTreeSet<String>,
+}
+
+impl ClaimDiff {
+    /// Check whether both user infos have the same claims.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ExtractUserInfo {
+    /// Diff the top-level claims of this user info with the other one, e.g. before and after a transform pipeline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let before = ExtractUserInfo(serde_json::json!({ "sub": "my-subject", "name": "my-name" }));
+    /// let after = ExtractUserInfo(serde_json::json!({ "sub": "my-subject", "tenant": "my-tenant" }));
+    ///
+    /// let diff = before.diff(&after);
+    ///
+    /// assert!(diff.added.contains("tenant"));
+    /// assert!(diff.removed.contains("name"));
+    /// assert!(diff.changed.is_empty());
+    /// ```
+    pub fn diff(&self, other: &ExtractUserInfo) -> ClaimDiff {
+        let empty = Map::new();
+        let before = self.0.as_object().unwrap_or(&empty);
+        let after = other.0.as_object().unwrap_or(&empty);
+
+        let mut diff = ClaimDiff::default();
+
+        for (key, value) in before {
+            match after.get(key) {
+                Some(other) if other != value => {
+                    diff.changed.insert(key.clone());
+                }
+                Some(_) => {}
+                None => {
+                    diff.removed.insert(key.clone());
+                }
+            }
+        }
+
+        diff.added = after
+            .keys()
+            .filter(|key| !before.contains_key(*key))
+            .cloned()
+            .collect();
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClaimDiff, ExtractUserInfo, fixtures};
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_diff_known_differences() {
+        let before = ExtractUserInfo(fixtures::user_info());
+        let mut after = before.clone();
+        after.merge(&json!({ "name": "my-other-name", "jti": null, "tenant": "my-tenant" }));
+
+        assert_eq!(
+            before.diff(&after),
+            ClaimDiff {
+                added: BTreeSet::from(["tenant".to_string()]),
+                removed: BTreeSet::from(["jti".to_string()]),
+                changed: BTreeSet::from(["name".to_string()]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_same() {
+        let user_info = ExtractUserInfo(fixtures::user_info());
+
+        assert!(user_info.diff(&user_info.clone()).is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::UserInfoRejection;
+use serde_json::Value;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+use tokio::sync::mpsc::Sender;
+
+/// This is the event sent for each extraction, e.g. to feed an out-of-band audit pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractionEvent {
+    /// The outcome of the extraction, i.e. its rejection if any.
+    pub outcome: Result<(), UserInfoRejection>,
+    /// The `sub` claim of the extracted user info, if any.
+    pub subject: Option<String>,
+    /// The time of the extraction.
+    pub timestamp: SystemTime,
+}
+
+/// This is the outcome of the extraction by `require_user_info_layer`, inserted into the response extensions with `outcome_extension`, e.g. for an access log layer running after the handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExtractionOutcome {
+    /// The user info was extracted, and the inner service called.
+    Extracted,
+    /// The request was rejected, with the given rejection, and the inner service not called.
+    Rejected(UserInfoRejection),
+}
+
+/// This is the bounded channel the extraction events are sent on, counting the events dropped when it's full.
+#[derive(Debug, Clone)]
+pub(crate) struct EventSender {
+    pub(crate) sender: Sender<ExtractionEvent>,
+    pub(crate) dropped: Arc<AtomicU64>,
+}
+
+impl EventSender {
+    /// Send the event of an extraction, without waiting, dropping it when the channel is full or closed.
+    pub(crate) fn send(&self, outcome: Result<&Value, &UserInfoRejection>) {
+        let event = ExtractionEvent {
+            outcome: outcome.map(|_| ()).map_err(Clone::clone),
+            subject: outcome.ok().and_then(|value| {
+                value
+                    .get("sub")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string)
+            }),
+            timestamp: SystemTime::now(),
+        };
+
+        if self.sender.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use crate::{
+    ExtractUserInfoRejection, UserInfoConfig, UserInfoRejection, extract::extract, lenient::Lenient,
+};
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderValue, header::AUTHORIZATION, request::Parts},
+    response::{IntoResponseParts, ResponseParts},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{convert::Infallible, time::Duration};
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with its decoded byte length.
+/// The decoded byte length is the size of the JSON payload once base 64 decoded, so it can be used for quota accounting without decoding it again.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithLength;
+///
+/// async fn handler(ExtractUserInfoWithLength(user_info, length): ExtractUserInfoWithLength) {
+///     println!("User Info: {:?} ({} bytes)", user_info, length);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithLength(pub Value, pub usize);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithLength
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithLength(
+            extraction.value,
+            extraction.decoded_len,
+        ))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, and propagates the `sub` claim as a `X-User-Id` response header.
+/// The `X-User-Id` header is set when the extractor is returned as part of the response, so it's both used as an argument and returned by the handler.
+/// If the `sub` claim is missing, not a string, or not a valid header value, no header is set.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Links
+///
+/// https://docs.rs/axum/latest/axum/response/trait.IntoResponseParts.html
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithUserId;
+///
+/// async fn handler(user_info: ExtractUserInfoWithUserId) -> (ExtractUserInfoWithUserId, &'static str) {
+///     println!("User Info: {:?}", user_info.0);
+///
+///     (user_info, "Hello")
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithUserId(pub Value);
+
+const HEADER_X_USER_ID: &str = "X-User-Id";
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithUserId
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithUserId(extraction.value))
+    }
+}
+
+impl IntoResponseParts for ExtractUserInfoWithUserId {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let user_id = self
+            .0
+            .get("sub")
+            .and_then(Value::as_str)
+            .and_then(|sub| HeaderValue::from_str(sub).ok());
+
+        if let Some(user_id) = user_id {
+            res.headers_mut().insert(HEADER_X_USER_ID, user_id);
+        }
+
+        Ok(res)
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with the time taken to decode and parse it.
+/// It lets handlers record the extraction cost in their own spans, for per-request performance attribution.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithDuration;
+///
+/// async fn handler(ExtractUserInfoWithDuration(user_info, duration): ExtractUserInfoWithDuration) {
+///     println!("User Info: {:?} (in {:?})", user_info, duration);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithDuration(pub Value, pub Duration);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithDuration
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithDuration(
+            extraction.value,
+            extraction.elapsed,
+        ))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with whether it was served from the cache.
+/// The cache is enabled via `UserInfoConfig::cache_extraction`, so it helps verifying it's effective in tests and dashboards.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithCacheHit;
+///
+/// async fn handler(ExtractUserInfoWithCacheHit(user_info, cache_hit): ExtractUserInfoWithCacheHit) {
+///     println!("User Info: {:?} (from cache: {})", user_info, cache_hit);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithCacheHit(pub Value, pub bool);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithCacheHit
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithCacheHit(
+            extraction.value,
+            extraction.cached,
+        ))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with whether it was validated, e.g. for a mixed shadow and enforced rollout.
+/// It's `true` when the whole validation pipeline ran and passed, including for the claims synthesized by `xfcc_fallback`, and `false` when a validation failed in shadow mode.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithValidation;
+///
+/// async fn handler(ExtractUserInfoWithValidation(user_info, validated): ExtractUserInfoWithValidation) {
+///     println!("User Info: {:?} (validated: {})", user_info, validated);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithValidation(pub Value, pub bool);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithValidation
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithValidation(
+            extraction.value,
+            extraction.validated,
+        ))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with the raw `Authorization` header, if any.
+/// It lets the handler forward the original bearer to the downstream services along with the decoded identity.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfoWithAuthorization;
+///
+/// async fn handler(ExtractUserInfoWithAuthorization(user_info, authorization): ExtractUserInfoWithAuthorization) {
+///     println!("User Info: {:?} (forwarding the bearer: {})", user_info, authorization.is_some());
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithAuthorization(pub Value, pub Option<HeaderValue>);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithAuthorization
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        Ok(ExtractUserInfoWithAuthorization(
+            extraction.value,
+            parts.headers.get(AUTHORIZATION).cloned(),
+        ))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, deserialized into the given type.
+/// The claims not known by the type can be kept, rather than dropped, by flattening them into a map, i.e. `#[serde(flatten)] extra: Map<String, Value>`.
+/// It rejects the request exactly as `ExtractUserInfo` does, and with a 400 Bad Request when the user info doesn't deserialize into the given type.
+/// The stringified numbers and booleans, as sent by some gateways, can be coerced via `UserInfoConfig::lenient_types`.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use serde::Deserialize;
+/// use serde_json::{Map, Value};
+/// use user_info_middleware::ExtractUserInfoAs;
+///
+/// #[derive(Deserialize)]
+/// struct UserInfo {
+///     sub: String,
+///     #[serde(flatten)]
+///     extra: Map<String, Value>,
+/// }
+///
+/// async fn handler(ExtractUserInfoAs(user_info): ExtractUserInfoAs<UserInfo>) {
+///     println!("User Info: {} ({} extra claim(s))", user_info.sub, user_info.extra.len());
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoAs<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ExtractUserInfoAs<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        let config = parts
+            .extensions
+            .get::<UserInfoConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        let user_info = if config.lenient_types {
+            T::deserialize(Lenient(extraction.value))
+        } else {
+            T::deserialize(extraction.value)
+        };
+
+        user_info
+            .map(ExtractUserInfoAs)
+            .map_err(|_| ExtractUserInfoRejection::new(UserInfoRejection::InvalidClaims, &config))
+    }
+}
+
+/// This is a custom extractor for Axum that extracts the user info, as `ExtractUserInfoAs` does, without ever rejecting the request.
+/// The typed user info, or its rejection, is wrapped in a `Result`, so the handler can render the error itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use serde_json::{Map, Value};
+/// use user_info_middleware::UserInfoAsResult;
+///
+/// async fn handler(UserInfoAsResult(user_info): UserInfoAsResult<Map<String, Value>>) -> String {
+///     match user_info {
+///         Ok(user_info) => format!("{} claim(s)", user_info.len()),
+///         Err(err) => format!("Anonymous ({})", err.reason()),
+///     }
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserInfoAsResult<T>(pub Result<T, UserInfoRejection>);
+
+impl<S, T> FromRequestParts<S> for UserInfoAsResult<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user_info = ExtractUserInfoAs::<T>::from_request_parts(parts, state).await;
+
+        Ok(UserInfoAsResult(
+            user_info
+                .map(|ExtractUserInfoAs(user_info)| user_info)
+                .map_err(|err| err.rejection().clone()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExtractUserInfoAs, ExtractUserInfoWithAuthorization, ExtractUserInfoWithCacheHit,
+        ExtractUserInfoWithDuration, ExtractUserInfoWithLength, ExtractUserInfoWithValidation,
+        UserInfoAsResult, UserInfoConfig, UserInfoRejection, fixtures,
+    };
+    use axum::{
+        extract::FromRequestParts,
+        http::{HeaderValue, StatusCode, header::AUTHORIZATION},
+    };
+    use base64::{Engine, engine::general_purpose};
+    use serde::Deserialize;
+    use serde_json::{Map, Value, json};
+    use std::{collections::BTreeMap, time::Instant};
+
+    /// This is a typed user info, with the unknown claims flattened into a map.
+    #[derive(Debug, Deserialize)]
+    struct TypedUserInfo {
+        sub: String,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    }
+
+    /// This is a typed user info, with a numeric claim, possibly stringified, along with the flattened unknown claims.
+    #[derive(Debug, Deserialize)]
+    struct IssuedUserInfo {
+        sub: String,
+        iat: i64,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_length() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let user_info = ExtractUserInfoWithLength::from_request_parts(&mut parts, &()).await;
+
+        match user_info {
+            Ok(ExtractUserInfoWithLength(user_info, length)) => {
+                assert_eq!(user_info, fixtures::user_info());
+                assert_eq!(
+                    length,
+                    general_purpose::STANDARD
+                        .decode(fixtures::USER_INFO)
+                        .unwrap()
+                        .len()
+                );
+            }
+            Err(err) => panic!("Expected a valid user info : {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_duration() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let start = Instant::now();
+        let user_info = ExtractUserInfoWithDuration::from_request_parts(&mut parts, &()).await;
+        let elapsed = start.elapsed();
+
+        match user_info {
+            Ok(ExtractUserInfoWithDuration(user_info, duration)) => {
+                assert_eq!(user_info, fixtures::user_info());
+                assert!(duration <= elapsed, "{:?} > {:?}", duration, elapsed);
+            }
+            Err(err) => panic!("Expected a valid user info : {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_cache_hit() {
+        let config = UserInfoConfig::new().cache_extraction(true);
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(config));
+
+        let first = ExtractUserInfoWithCacheHit::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        let second = ExtractUserInfoWithCacheHit::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(first.0, fixtures::user_info());
+        assert!(!first.1);
+        assert_eq!(second.0, fixtures::user_info());
+        assert!(second.1);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_cache_disabled() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let _ = ExtractUserInfoWithCacheHit::from_request_parts(&mut parts, &()).await;
+        let second = ExtractUserInfoWithCacheHit::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert!(!second.1);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_validation() {
+        let encoded = fixtures::encode(&json!([1]));
+        let enforce = UserInfoConfig::new().require_object(true);
+        let shadow = enforce.clone().shadow_mode(true);
+
+        let mut enforced_parts = fixtures::parts(Some(fixtures::USER_INFO), Some(enforce.clone()));
+        let mut rejected_parts = fixtures::parts(Some(&encoded), Some(enforce));
+        let mut shadow_passed_parts =
+            fixtures::parts(Some(fixtures::USER_INFO), Some(shadow.clone()));
+        let mut shadow_failed_parts = fixtures::parts(Some(&encoded), Some(shadow));
+
+        let enforced =
+            ExtractUserInfoWithValidation::from_request_parts(&mut enforced_parts, &()).await;
+        let rejected =
+            ExtractUserInfoWithValidation::from_request_parts(&mut rejected_parts, &()).await;
+        let shadow_passed =
+            ExtractUserInfoWithValidation::from_request_parts(&mut shadow_passed_parts, &()).await;
+        let shadow_failed =
+            ExtractUserInfoWithValidation::from_request_parts(&mut shadow_failed_parts, &()).await;
+
+        assert!(enforced.unwrap().1);
+        assert_eq!(rejected.unwrap_err(), UserInfoRejection::NotObject);
+        assert!(shadow_passed.unwrap().1);
+
+        let ExtractUserInfoWithValidation(user_info, validated) = shadow_failed.unwrap();
+
+        assert_eq!(user_info, json!([1]));
+        assert!(!validated);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_validation_xfcc_fallback() {
+        let config = UserInfoConfig::new().xfcc_fallback(true);
+        let mut parts = fixtures::parts(None, Some(config));
+        parts.headers.insert(
+            "X-Forwarded-Client-Cert",
+            HeaderValue::from_static("URI=spiffe://cluster.local/ns/foo/sa/my-service"),
+        );
+
+        let ExtractUserInfoWithValidation(user_info, validated) =
+            ExtractUserInfoWithValidation::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            user_info["sub"],
+            "spiffe://cluster.local/ns/foo/sa/my-service"
+        );
+        assert!(validated);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_with_extra_claims() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let user_info =
+            ExtractUserInfoAs::<TypedUserInfo>::from_request_parts(&mut parts, &()).await;
+
+        match user_info {
+            Ok(ExtractUserInfoAs(user_info)) => {
+                let mut extra = fixtures::user_info().as_object().unwrap().clone();
+                extra.remove("sub");
+
+                assert_eq!(user_info.sub, "my-subject");
+                assert_eq!(user_info.extra, extra);
+                assert_eq!(user_info.extra["iss"], json!("my-issuer"));
+            }
+            Err(err) => panic!("Expected a valid user info : {:?}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_with_extra_claims_lenient() {
+        let config = UserInfoConfig::new().lenient_types(true);
+        let encoded = fixtures::encode(&json!({
+            "sub": "my-subject",
+            "iat": "1516239022",
+            "admin": "true"
+        }));
+        let mut strict_parts = fixtures::parts(Some(&encoded), None);
+        let mut parts = fixtures::parts(Some(&encoded), Some(config));
+
+        let strict =
+            ExtractUserInfoAs::<IssuedUserInfo>::from_request_parts(&mut strict_parts, &()).await;
+
+        assert_eq!(strict.unwrap_err(), UserInfoRejection::InvalidClaims);
+
+        let user_info =
+            ExtractUserInfoAs::<IssuedUserInfo>::from_request_parts(&mut parts, &()).await;
+
+        let ExtractUserInfoAs(user_info) = user_info.unwrap();
+
+        assert_eq!(user_info.sub, "my-subject");
+        assert_eq!(user_info.iat, 1516239022);
+        assert_eq!(
+            user_info.extra,
+            json!({ "admin": "true" }).as_object().unwrap().clone()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_ko_invalid_claims() {
+        let encoded = fixtures::encode(&json!({ "name": "my-name" }));
+        let mut parts = fixtures::parts(Some(&encoded), None);
+
+        let user_info =
+            ExtractUserInfoAs::<TypedUserInfo>::from_request_parts(&mut parts, &()).await;
+
+        match user_info {
+            Ok(_) => panic!("Expected an error"),
+            Err(err) => assert_eq!(err, UserInfoRejection::InvalidClaims),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_shape_error_status() {
+        let config = UserInfoConfig::new().shape_error_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let encoded = fixtures::encode(&json!({ "name": "my-name" }));
+
+        let mut shape_parts = fixtures::parts(Some(&encoded), Some(config.clone()));
+        let mut transport_parts = fixtures::parts(Some("not base 64 !"), Some(config));
+        let mut default_parts = fixtures::parts(Some(&encoded), None);
+
+        let shape = ExtractUserInfoAs::<TypedUserInfo>::from_request_parts(&mut shape_parts, &())
+            .await
+            .unwrap_err();
+        let transport =
+            ExtractUserInfoAs::<TypedUserInfo>::from_request_parts(&mut transport_parts, &())
+                .await
+                .unwrap_err();
+        let default =
+            ExtractUserInfoAs::<TypedUserInfo>::from_request_parts(&mut default_parts, &())
+                .await
+                .unwrap_err();
+
+        assert_eq!(shape, UserInfoRejection::InvalidClaims);
+        assert_eq!(shape.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(transport, UserInfoRejection::NotBase64);
+        assert_eq!(transport.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(default.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_lenient_number() {
+        let config = UserInfoConfig::new().lenient_types(true);
+        let encoded = fixtures::encode(&json!({ "iat": "1516239022", "exp": 1516239022 }));
+        let mut parts = fixtures::parts(Some(&encoded), Some(config));
+
+        let user_info =
+            ExtractUserInfoAs::<BTreeMap<String, i64>>::from_request_parts(&mut parts, &()).await;
+
+        let ExtractUserInfoAs(user_info) = user_info.unwrap();
+
+        assert_eq!(user_info["iat"], 1516239022);
+        assert_eq!(user_info["exp"], 1516239022);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_lenient_boolean() {
+        let config = UserInfoConfig::new().lenient_types(true);
+        let encoded = fixtures::encode(&json!({ "email_verified": "true", "admin": false }));
+        let mut parts = fixtures::parts(Some(&encoded), Some(config));
+
+        let user_info =
+            ExtractUserInfoAs::<BTreeMap<String, bool>>::from_request_parts(&mut parts, &()).await;
+
+        let ExtractUserInfoAs(user_info) = user_info.unwrap();
+
+        assert!(user_info["email_verified"]);
+        assert!(!user_info["admin"]);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_as_strict() {
+        let encoded = fixtures::encode(&json!({ "iat": "1516239022" }));
+        let mut parts = fixtures::parts(Some(&encoded), None);
+
+        let user_info =
+            ExtractUserInfoAs::<BTreeMap<String, i64>>::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(user_info.unwrap_err(), UserInfoRejection::InvalidClaims);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_user_info_as_result_ok() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let UserInfoAsResult(user_info) =
+            UserInfoAsResult::<TypedUserInfo>::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_info.unwrap().sub, "my-subject");
+    }
+
+    #[tokio::test]
+    async fn test_extractors_user_info_as_result_err() {
+        let mut parts = fixtures::parts(Some("this-is-not-a-base64"), None);
+
+        let UserInfoAsResult(user_info) =
+            UserInfoAsResult::<TypedUserInfo>::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_info.unwrap_err(), UserInfoRejection::NotBase64);
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_with_authorization() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+        parts
+            .headers
+            .insert(AUTHORIZATION, HeaderValue::from_static("Bearer my-token"));
+
+        let ExtractUserInfoWithAuthorization(user_info, authorization) =
+            ExtractUserInfoWithAuthorization::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_info, fixtures::user_info());
+        assert_eq!(
+            authorization,
+            Some(HeaderValue::from_static("Bearer my-token"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extractors_extract_user_info_without_authorization() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let ExtractUserInfoWithAuthorization(user_info, authorization) =
+            ExtractUserInfoWithAuthorization::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_info, fixtures::user_info());
+        assert_eq!(authorization, None);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    ExtractUserInfo, ExtractUserInfoRejection,
+    extract::extract,
+    sha256::{sha256, to_hex},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, along with a stable fingerprint of the request, e.g. as an idempotency or a dedup key.
+/// The fingerprint is the hexadecimal SHA-256 of the canonical claims, i.e. with their keys sorted at any depth, the request method and the request path, its query aside.
+/// So two requests of the same identity to the same method and path share the same fingerprint, whatever the order of the claims.
+/// It rejects the request exactly as `ExtractUserInfo` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::post, Router};
+/// use user_info_middleware::ExtractUserInfoWithFingerprint;
+///
+/// async fn handler(ExtractUserInfoWithFingerprint(user_info, fingerprint): ExtractUserInfoWithFingerprint) {
+///     println!("User Info: {:?} (dedup key: {})", user_info, fingerprint);
+/// }
+///
+/// let app = Router::<()>::new().route("/orders", post(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfoWithFingerprint(pub Value, pub String);
+
+impl<S> FromRequestParts<S> for ExtractUserInfoWithFingerprint
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        let mut canonical = String::new();
+        write_canonical(&extraction.value, &mut canonical);
+
+        let input = format!("{}\n{}\n{}", canonical, parts.method, parts.uri.path());
+        let fingerprint = to_hex(&sha256(input.as_bytes()));
+
+        Ok(ExtractUserInfoWithFingerprint(
+            extraction.value,
+            fingerprint,
+        ))
+    }
+}
+
+impl ExtractUserInfo {
+    /// Get the hexadecimal SHA-256 of the `sub` claim, i.e. a stable and non-reversible correlation id of the user, e.g. for the distributed tracing.
+    /// As it isn't salted, a guessable subject, e.g. an email, can still be found via a dictionary attack.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "sub": "my-subject" }));
+    ///
+    /// assert_eq!(user_info.subject_hash().unwrap().len(), 64);
+    /// ```
+    pub fn subject_hash(&self) -> Option<String> {
+        let sub = self.0.get("sub")?.as_str()?;
+
+        Some(to_hex(&sha256(sub.as_bytes())))
+    }
+}
+
+/// Write the value as its canonical JSON, i.e. compact and with the keys of the objects sorted at any depth.
+fn write_canonical(value: &Value, canonical: &mut String) {
+    match value {
+        Value::Array(values) => {
+            canonical.push('[');
+
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    canonical.push(',');
+                }
+
+                write_canonical(value, canonical);
+            }
+
+            canonical.push(']');
+        }
+        Value::Object(claims) => {
+            let mut claims: Vec<_> = claims.iter().collect();
+            claims.sort_by_key(|(key, _)| *key);
+
+            canonical.push('{');
+
+            for (index, (key, value)) in claims.into_iter().enumerate() {
+                if index > 0 {
+                    canonical.push(',');
+                }
+
+                canonical.push_str(&Value::String(key.clone()).to_string());
+                canonical.push(':');
+                write_canonical(value, canonical);
+            }
+
+            canonical.push('}');
+        }
+        scalar => canonical.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractUserInfo, ExtractUserInfoWithFingerprint, fixtures};
+    use axum::{body::Body, extract::FromRequestParts, http::Request};
+    use serde_json::json;
+
+    async fn fingerprint(method: &str, uri: &str, user_info: &str) -> String {
+        let mut parts = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Endpoint-API-UserInfo", user_info)
+            .body(Body::empty())
+            .unwrap()
+            .into_parts()
+            .0;
+
+        ExtractUserInfoWithFingerprint::from_request_parts(&mut parts, &())
+            .await
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_fingerprint_subject_hash() {
+        let user_info = ExtractUserInfo(json!({ "sub": "my-subject" }));
+
+        assert_eq!(
+            user_info.subject_hash().as_deref(),
+            Some("f8667cd3dc63562e02523d52bfb8a41a85df4e30752d64511d15fca461c1ed02")
+        );
+        assert_eq!(ExtractUserInfo(json!({ "sub": 1 })).subject_hash(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_identical_requests() {
+        let first = fingerprint("POST", "/orders?retry=1", fixtures::USER_INFO).await;
+        let second = fingerprint("POST", "/orders", fixtures::USER_INFO).await;
+
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_claims_order() {
+        // {"sub":"my-subject","name":"my-name"} and {"name":"my-name","sub":"my-subject"}
+        let ordered = "eyJzdWIiOiJteS1zdWJqZWN0IiwibmFtZSI6Im15LW5hbWUifQ==";
+        let reordered = "eyJuYW1lIjoibXktbmFtZSIsInN1YiI6Im15LXN1YmplY3QifQ==";
+
+        assert_eq!(
+            fingerprint("GET", "/foo", ordered).await,
+            fingerprint("GET", "/foo", reordered).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint_differing_requests() {
+        let reference = fingerprint("POST", "/orders", fixtures::USER_INFO).await;
+        let other_user = fixtures::encode(&json!({ "sub": "my-other-subject" }));
+
+        assert_ne!(
+            reference,
+            fingerprint("PUT", "/orders", fixtures::USER_INFO).await
+        );
+        assert_ne!(
+            reference,
+            fingerprint("POST", "/carts", fixtures::USER_INFO).await
+        );
+        assert_ne!(reference, fingerprint("POST", "/orders", &other_user).await);
+    }
+}
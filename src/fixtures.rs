@@ -0,0 +1,71 @@
+use crate::UserInfoConfig;
+use axum::{
+    body::Body,
+    http::{Request, request::Parts},
+};
+use proptest::prelude::*;
+use serde_json::Value;
+
+/// This is the base 64 encoded JSON used across the tests.
+pub(crate) const USER_INFO: &str = "eyJpc3MiOiJteS1pc3N1ZXIiLCJzdWIiOiJteS1zdWJqZWN0IiwiYXVkIjoibXktYXVkaWVuY2UiLCJuYW1lIjoibXktbmFtZSIsImlhdCI6MTUxNjIzOTAyMiwiZXhwIjoxNTE2MjM5MDIyLCJuYmYiOjE1MTYyMzkwMjIsImp0aSI6Im15LXVuaXF1ZS1pZCJ9";
+
+/// This is the decoded value of `USER_INFO`.
+pub(crate) fn user_info() -> Value {
+    serde_json::json!({
+      "iss": "my-issuer",
+      "sub": "my-subject",
+      "aud": "my-audience",
+      "name": "my-name",
+      "iat": 1516239022,
+      "exp": 1516239022,
+      "nbf": 1516239022,
+      "jti": "my-unique-id"
+    })
+}
+
+/// Encode a JSON value as expected in the `X-Endpoint-API-UserInfo` header.
+pub(crate) fn encode(value: &Value) -> String {
+    use base64::{Engine, engine::general_purpose};
+
+    general_purpose::STANDARD.encode(value.to_string())
+}
+
+/// Build the request parts with an optional `X-Endpoint-API-UserInfo` header and an optional configuration.
+pub(crate) fn parts(user_info: Option<&str>, config: Option<UserInfoConfig>) -> Parts {
+    let mut request = Request::builder();
+
+    if let Some(user_info) = user_info {
+        request = request.header("X-Endpoint-API-UserInfo", user_info);
+    }
+
+    if let Some(config) = config {
+        request = request.extension(config);
+    }
+
+    request.body(Body::empty()).unwrap().into_parts().0
+}
+
+/// Build the strategy of arbitrary JSON values, for the round-trip property tests.
+/// The strings cover any unicode, e.g. the JSON escapes, the control characters and the multi-byte characters, while the numbers cover the edges of their representations.
+/// The floats are dyadic fractions of at most 15 significant digits, as the default `serde_json` parse, i.e. without its `float_roundtrip` feature, may be off by one ULP beyond.
+/// The nesting is bounded, so the failing values shrink towards the leaves.
+pub(crate) fn arbitrary_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        (any::<i16>(), 0..8u32).prop_map(|(mantissa, exponent)| Value::from(
+            f64::from(mantissa) / f64::from(1 << exponent)
+        )),
+        any::<String>().prop_map(Value::String),
+    ];
+
+    leaf.prop_recursive(4, 64, 8, |value| {
+        prop_oneof![
+            prop::collection::vec(value.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::vec((any::<String>(), value), 0..8)
+                .prop_map(|claims| Value::Object(claims.into_iter().collect())),
+        ]
+    })
+}
@@ -0,0 +1,191 @@
+use crate::{
+    ExtractUserInfo, ExtractUserInfoRejection, UserInfoConfig, UserInfoRejection, extract::extract,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// This is a custom extractor for Axum that extracts an owned identity from the user info, i.e. `'static` and `Send`, so it can be moved into a spawned task.
+/// It maps the following claims, ignoring the other ones :
+///
+/// - `sub` : the subject, required ;
+/// - `iss` : the issuer ;
+/// - `aud` : the audiences, as returned by `ExtractUserInfo::audience` ;
+/// - `email` and `name` : the email and the display name ;
+/// - `groups` : the groups, as returned by `ExtractUserInfo::groups` ;
+/// - `scope` and `scp` : the scopes, as returned by `ExtractUserInfo::scopes` ;
+/// - `iat` and `exp` : the issuance and expiration times, in seconds since the UNIX epoch.
+///
+/// It rejects the request exactly as `ExtractUserInfo` does, and with a 400 Bad Request when the `sub` claim is missing or isn't a string.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::OwnedIdentity;
+///
+/// async fn handler(identity: OwnedIdentity) {
+///     tokio::spawn(async move {
+///         println!("Auditing : {}", identity.subject);
+///     });
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedIdentity {
+    /// The subject, from the `sub` claim.
+    pub subject: String,
+    /// The issuer, from the `iss` claim, if any.
+    pub issuer: Option<String>,
+    /// The audiences, from the `aud` claim.
+    pub audiences: Vec<String>,
+    /// The email, from the `email` claim, if any.
+    pub email: Option<String>,
+    /// The display name, from the `name` claim, if any.
+    pub name: Option<String>,
+    /// The groups, from the `groups` claim.
+    pub groups: Vec<String>,
+    /// The scopes, from the `scope` and `scp` claims.
+    pub scopes: BTreeSet<String>,
+    /// The issuance time, from the `iat` claim, if any.
+    pub issued_at: Option<u64>,
+    /// The expiration time, from the `exp` claim, if any.
+    pub expires_at: Option<u64>,
+}
+
+impl OwnedIdentity {
+    /// Build the owned identity from the claims, or `None` when the `sub` claim is missing or isn't a string.
+    pub(crate) fn from_claims(user_info: &ExtractUserInfo) -> Option<Self> {
+        let claims = &user_info.0;
+        let string = |claim: &str| claims.get(claim).and_then(Value::as_str).map(String::from);
+
+        Some(OwnedIdentity {
+            subject: string("sub")?,
+            issuer: string("iss"),
+            audiences: user_info.audience().into_iter().map(String::from).collect(),
+            email: string("email"),
+            name: string("name"),
+            groups: user_info.groups().into_iter().map(String::from).collect(),
+            scopes: user_info.scopes(),
+            issued_at: claims.get("iat").and_then(Value::as_u64),
+            expires_at: claims.get("exp").and_then(Value::as_u64),
+        })
+    }
+}
+
+impl<S> FromRequestParts<S> for OwnedIdentity
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        OwnedIdentity::from_claims(&ExtractUserInfo(extraction.value)).ok_or_else(|| {
+            let config = parts
+                .extensions
+                .get::<UserInfoConfig>()
+                .cloned()
+                .unwrap_or_default();
+
+            ExtractUserInfoRejection::new(
+                UserInfoRejection::MissingClaim {
+                    claim: "sub".to_string(),
+                },
+                &config,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OwnedIdentity, UserInfoRejection, fixtures};
+    use axum::extract::FromRequestParts;
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[tokio::test]
+    async fn test_identity_owned_identity_representative() {
+        let user_info = json!({
+            "iss": "my-issuer",
+            "sub": "my-subject",
+            "aud": ["my-audience", "my-other-audience"],
+            "email": "my-email@nebeto.xyz",
+            "name": "my-name",
+            "groups": ["admin", "users"],
+            "scope": "read write",
+            "iat": 1516239022,
+            "exp": 1516242622,
+            "custom": "ignored"
+        });
+        let mut parts = fixtures::parts(Some(&fixtures::encode(&user_info)), None);
+
+        let identity = OwnedIdentity::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            identity,
+            OwnedIdentity {
+                subject: "my-subject".to_string(),
+                issuer: Some("my-issuer".to_string()),
+                audiences: vec!["my-audience".to_string(), "my-other-audience".to_string()],
+                email: Some("my-email@nebeto.xyz".to_string()),
+                name: Some("my-name".to_string()),
+                groups: vec!["admin".to_string(), "users".to_string()],
+                scopes: BTreeSet::from(["read".to_string(), "write".to_string()]),
+                issued_at: Some(1516239022),
+                expires_at: Some(1516242622),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_identity_owned_identity_moved_into_task() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let identity = OwnedIdentity::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        let subject = tokio::spawn(async move { identity.subject }).await.unwrap();
+
+        assert_eq!(subject, "my-subject");
+    }
+
+    #[tokio::test]
+    async fn test_identity_owned_identity_minimal() {
+        let mut parts = fixtures::parts(
+            Some(&fixtures::encode(&json!({ "sub": "my-subject" }))),
+            None,
+        );
+
+        let identity = OwnedIdentity::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identity.subject, "my-subject");
+        assert_eq!(identity.issuer, None);
+        assert!(identity.audiences.is_empty());
+        assert!(identity.groups.is_empty());
+        assert!(identity.scopes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identity_owned_identity_without_subject() {
+        let mut parts =
+            fixtures::parts(Some(&fixtures::encode(&json!({ "name": "my-name" }))), None);
+
+        let identity = OwnedIdentity::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            identity.unwrap_err(),
+            UserInfoRejection::MissingClaim {
+                claim: "sub".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{
+    ExtractUserInfo, ExtractUserInfoRejection, ExtractionOutcome, HEADER_X_USER_INFO,
+    UserInfoConfig, UserInfoRejection, context::CONTEXT, extract::extract, logging::log_error,
+};
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The default cap, in bytes, of the body buffered by the `trailer_fallback`, as the default body limit of axum.
+const DEFAULT_TRAILER_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Build a layer enforcing a valid user info, via the `X-Endpoint-API-UserInfo` header, on every request.
+/// A request without a valid user info is rejected exactly as `ExtractUserInfo` does, the inner service isn't called.
+/// A request with a valid user info gets the configuration and an `ExtractUserInfo` in its extensions, so handlers can use `Extension<ExtractUserInfo>`.
+///
+/// As it applies to every request of the router, public routes must be kept out of it.
+/// Either apply it via `route_layer` on a router holding the protected routes only, then merge the public ones, or nest the protected routes.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Extension, Router};
+/// use user_info_middleware::{require_user_info_layer, ExtractUserInfo, UserInfoConfig};
+///
+/// async fn protected(Extension(ExtractUserInfo(user_info)): Extension<ExtractUserInfo>) {
+///     println!("User Info: {:?}", user_info);
+/// }
+///
+/// async fn public() {}
+///
+/// let app = Router::<()>::new()
+///     .route("/foo", get(protected))
+///     .route_layer(require_user_info_layer(UserInfoConfig::new()))
+///     .merge(Router::new().route("/health", get(public)));
+/// ```
+pub fn require_user_info_layer(config: UserInfoConfig) -> RequireUserInfoLayer {
+    RequireUserInfoLayer { config }
+}
+
+/// This is the layer built by `require_user_info_layer`.
+#[derive(Debug, Clone)]
+pub struct RequireUserInfoLayer {
+    config: UserInfoConfig,
+}
+
+impl<S> Layer<S> for RequireUserInfoLayer {
+    type Service = RequireUserInfo<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireUserInfo {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// This is the service built by `RequireUserInfoLayer`.
+#[derive(Debug, Clone)]
+pub struct RequireUserInfo<S> {
+    inner: S,
+    config: UserInfoConfig,
+}
+
+impl<S> Service<Request<Body>> for RequireUserInfo<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let (mut parts, mut body) = request.into_parts();
+
+            if config.trailer_fallback && !parts.headers.contains_key(HEADER_X_USER_INFO) {
+                let max = config
+                    .trailer_body_limit
+                    .unwrap_or(DEFAULT_TRAILER_BODY_LIMIT);
+
+                let collected = match Limited::new(body, max).collect().await {
+                    Ok(collected) => collected,
+                    Err(err) if err.is::<LengthLimitError>() => {
+                        log_error!(
+                            config,
+                            "[{}] Failed to read the body for its trailers due to : Body exceeding {} bytes",
+                            HEADER_X_USER_INFO,
+                            max
+                        );
+
+                        return Ok(reject(UserInfoRejection::BodyTooLarge { max }, &config));
+                    }
+                    Err(err) => {
+                        log_error!(
+                            config,
+                            "[{}] Failed to read the body for its trailers due to : {:?}",
+                            HEADER_X_USER_INFO,
+                            err
+                        );
+
+                        return Ok(reject(UserInfoRejection::NotFound, &config));
+                    }
+                };
+
+                if let Some(user_info) = collected
+                    .trailers()
+                    .and_then(|trailers| trailers.get(HEADER_X_USER_INFO))
+                {
+                    parts.headers.insert(HEADER_X_USER_INFO, user_info.clone());
+                }
+
+                body = Body::from(collected.to_bytes());
+            }
+
+            let subject_hash_header = config
+                .subject_hash_header
+                .as_deref()
+                .and_then(|header| HeaderName::from_bytes(header.as_bytes()).ok());
+
+            let context_claims = config.context_claims.clone();
+            let outcome_extension = config.outcome_extension;
+
+            parts.extensions.insert(config);
+
+            match extract(&mut parts).await {
+                Ok(extraction) => {
+                    let user_info = ExtractUserInfo(extraction.value);
+                    let subject_hash = subject_hash_header.clone().zip(
+                        user_info
+                            .subject_hash()
+                            .and_then(|hash| HeaderValue::from_str(&hash).ok()),
+                    );
+
+                    // Never forward a client supplied value, e.g. when there is no `sub` to hash.
+                    if let Some(header) = &subject_hash_header {
+                        parts.headers.remove(header);
+                    }
+
+                    if let Some((header, hash)) = &subject_hash {
+                        parts.headers.insert(header.clone(), hash.clone());
+                    }
+
+                    let context = context_claims.map(|claims| {
+                        claims
+                            .into_iter()
+                            .filter_map(|claim| {
+                                let value = user_info.0.get(&claim)?.clone();
+
+                                Some((claim, value))
+                            })
+                            .collect()
+                    });
+
+                    parts.extensions.insert(user_info);
+
+                    let call = inner.call(Request::from_parts(parts, body));
+
+                    let mut response = match context {
+                        Some(context) => CONTEXT.scope(context, call).await?,
+                        None => call.await?,
+                    };
+
+                    if let Some((header, hash)) = subject_hash {
+                        response.headers_mut().insert(header, hash);
+                    }
+
+                    if outcome_extension {
+                        response
+                            .extensions_mut()
+                            .insert(ExtractionOutcome::Extracted);
+                    }
+
+                    Ok(response)
+                }
+                Err(rejection) => {
+                    let outcome = ExtractionOutcome::Rejected(rejection.rejection().clone());
+                    let mut response = rejection.into_response();
+
+                    if outcome_extension {
+                        response.extensions_mut().insert(outcome);
+                    }
+
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+/// Render the rejection as a response, along with its `ExtractionOutcome` when enabled.
+fn reject(rejection: UserInfoRejection, config: &UserInfoConfig) -> Response {
+    let mut response = ExtractUserInfoRejection::new(rejection.clone(), config).into_response();
+
+    if config.outcome_extension {
+        response
+            .extensions_mut()
+            .insert(ExtractionOutcome::Rejected(rejection));
+    }
+
+    response
+}
@@ -0,0 +1,138 @@
+use crate::{HEADER_X_USER_INFO, UserInfoConfig, UserInfoRejection, extract::parse};
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, request::Parts},
+};
+use serde_json::Value;
+use std::{convert::Infallible, sync::OnceLock};
+
+/// This is a custom extractor for Axum that holds the raw `X-Endpoint-API-UserInfo` header, decoding it only on demand.
+/// It never rejects the request, the decoding happens on the first call to `get`, and its result is cached for the following calls.
+/// It's useful for handlers which may not need the user info, e.g. on a cache hit.
+///
+/// As the decoding is synchronous, only its own stages are run, along with the ones depending on the request headers, i.e. the detached signature check and the trust boundary filter,
+/// but neither the subject check against the bearer nor the user lookup.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::LazyUserInfo;
+///
+/// async fn handler(user_info: LazyUserInfo) {
+///     if let Ok(user_info) = user_info.get() {
+///         println!("User Info: {:?}", user_info);
+///     }
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug)]
+pub struct LazyUserInfo {
+    headers: HeaderMap,
+    config: UserInfoConfig,
+    decoded: OnceLock<Result<Value, UserInfoRejection>>,
+}
+
+impl LazyUserInfo {
+    /// Decode the user info on the first call, then return the cached result.
+    pub fn get(&self) -> Result<Value, UserInfoRejection> {
+        self.decoded
+            .get_or_init(|| {
+                parse(
+                    self.headers.get(HEADER_X_USER_INFO),
+                    &self.headers,
+                    &self.config,
+                )
+                .map(|extraction| extraction.value)
+            })
+            .clone()
+    }
+
+    /// Check whether the user info has already been decoded.
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+}
+
+impl<S> FromRequestParts<S> for LazyUserInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(LazyUserInfo {
+            headers: parts.headers.clone(),
+            config: parts
+                .extensions
+                .get::<UserInfoConfig>()
+                .cloned()
+                .unwrap_or_default(),
+            decoded: OnceLock::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LazyUserInfo, UserInfoConfig, UserInfoRejection, fixtures, test_logger};
+    use axum::extract::FromRequestParts;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_lazy_user_info_get() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let user_info = LazyUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert!(!user_info.is_decoded());
+        assert_eq!(user_info.get(), Ok(fixtures::user_info()));
+        assert!(user_info.is_decoded());
+        assert_eq!(user_info.get(), Ok(fixtures::user_info()));
+    }
+
+    #[tokio::test]
+    async fn test_lazy_user_info_get_decoded_once() {
+        test_logger::init();
+
+        let mut parts = fixtures::parts(Some("this-is-not-a-base64"), None);
+
+        let user_info = LazyUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert!(test_logger::records().is_empty());
+        assert_eq!(user_info.get(), Err(UserInfoRejection::NotBase64));
+        assert_eq!(user_info.get(), Err(UserInfoRejection::NotBase64));
+        assert_eq!(test_logger::records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_user_info_get_hmac_signature_forged() {
+        let config = UserInfoConfig::new().hmac_signature("X-UserInfo-Signature", "my-key");
+        let forged = fixtures::encode(&json!({ "sub": "admin", "role": "root" }));
+        let mut parts = fixtures::parts(Some(&forged), Some(config));
+
+        let user_info = LazyUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info.get(), Err(UserInfoRejection::SignatureMismatch));
+    }
+
+    #[tokio::test]
+    async fn test_lazy_user_info_get_trust_boundary_untrusted() {
+        let config = UserInfoConfig::new().trust_boundary("X-Proxy-Secret", "my-secret", ["sub"]);
+        let user_info = fixtures::encode(&json!({ "sub": "admin", "role": "root" }));
+        let mut parts = fixtures::parts(Some(&user_info), Some(config));
+
+        let user_info = LazyUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info.get(), Ok(json!({ "sub": "admin" })));
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{
+    de::{
+        DeserializeSeed, Deserializer, EnumAccess, Error as _, IntoDeserializer, MapAccess,
+        SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::{Error, Map, Value};
+
+/// This is a lenient deserializer of a JSON value, coercing the numeric and boolean strings, e.g. `"1516239022"` or `"true"`, when a number or a boolean is expected.
+/// The coercion applies at any depth, while any other value is deserialized as `serde_json` does.
+pub(crate) struct Lenient(pub(crate) Value);
+
+impl Lenient {
+    fn deserialize_number<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(string) => {
+                let number = string.trim();
+
+                if let Ok(number) = number.parse::<i64>() {
+                    visitor.visit_i64(number)
+                } else if let Ok(number) = number.parse::<u64>() {
+                    visitor.visit_u64(number)
+                } else if let Ok(number) = number.parse::<f64>() {
+                    visitor.visit_f64(number)
+                } else {
+                    Value::String(string).deserialize_any(visitor)
+                }
+            }
+            value => Lenient(value).deserialize_any(visitor),
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.deserialize_number(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(values) => visitor.visit_seq(LenientSeq(values.into_iter())),
+            Value::Object(claims) => visitor.visit_map(LenientMap {
+                claims: claims.into_iter(),
+                value: None,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(string) => match string.trim() {
+                "true" => visitor.visit_bool(true),
+                "false" => visitor.visit_bool(false),
+                _ => Value::String(string).deserialize_any(visitor),
+            },
+            value => Lenient(value).deserialize_any(visitor),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Lenient(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Object(claims) if claims.len() == 1 => {
+                visitor.visit_enum(LenientEnum(claims.into_iter().next()))
+            }
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct LenientSeq(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for LenientSeq {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(Lenient(value)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct LenientMap {
+    claims: <Map<String, Value> as IntoIterator>::IntoIter,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for LenientMap {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.claims.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Lenient(value)),
+            None => Err(Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.claims.len())
+    }
+}
+
+struct LenientEnum(Option<(String, Value)>);
+
+impl<'de> EnumAccess<'de> for LenientEnum {
+    type Error = Error;
+    type Variant = Lenient;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Lenient), Error> {
+        let (variant, value) = self.0.ok_or_else(|| Error::custom("variant is missing"))?;
+
+        Ok((
+            seed.deserialize(variant.into_deserializer())?,
+            Lenient(value),
+        ))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for Lenient {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        serde::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lenient::Lenient;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_lenient_nested_numbers() {
+        let value = json!({ "times": { "iat": "1516239022", "exp": 1516239022 } });
+
+        let times = BTreeMap::<String, BTreeMap<String, i64>>::deserialize(Lenient(value)).unwrap();
+        let ratios = Vec::<f64>::deserialize(Lenient(json!([" 0.5 ", 1]))).unwrap();
+
+        assert_eq!(times["times"]["iat"], 1516239022);
+        assert_eq!(times["times"]["exp"], 1516239022);
+        assert_eq!(ratios, vec![0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_lenient_not_coercible() {
+        let result = BTreeMap::<String, bool>::deserialize(Lenient(json!({ "verified": "yes" })));
+
+        assert!(result.is_err());
+    }
+}
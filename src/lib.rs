@@ -1,10 +1,92 @@
-use axum::{
-    extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+use axum::{extract::FromRequestParts, http::request::Parts};
+use extract::extract;
+use serde_json::{Map, Value};
+
+mod audience;
+mod bearer;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod check;
+mod claims;
+mod config;
+mod consumer;
+mod context;
+mod decode;
+mod diagnose;
+mod diff;
+mod events;
+mod extract;
+mod extractors;
+mod fingerprint;
+#[cfg(test)]
+mod fixtures;
+mod identity;
+mod layer;
+mod lazy;
+mod lenient;
+mod logging;
+mod multi;
+mod nonce;
+mod owner;
+mod provider;
+mod query;
+mod redact;
+mod rejection;
+mod require;
+mod sha256;
+mod signature;
+mod subject;
+mod tenant;
+#[cfg(test)]
+mod test_logger;
+#[cfg(test)]
+mod test_subscriber;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod transform;
+mod trust;
+mod validate;
+mod verified;
+mod xfcc;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use audience::SharedAudiences;
+#[cfg(feature = "cbor")]
+pub use cbor::ExtractCborUserInfo;
+pub use check::{ConfigError, validate_config};
+pub use config::{
+    AudienceObjectHandling, ControlCharacterHandling, EmptyArrayHandling, ExpirationBoundary,
+    HeaderEncoding, HeavyDecodeOverflow, JsonType, MultiIdentityHandling, UserInfoConfig,
 };
-use base64::{Engine, engine::general_purpose};
-use log::error;
-use serde_json::{self, Value};
+pub use consumer::{ExtractConsumerInfo, ExtractUserAndConsumerInfo};
+pub use context::context_claim;
+pub use diagnose::{Base64Alphabet, DecodeReport, decode_and_parse};
+pub use diff::ClaimDiff;
+pub use events::{ExtractionEvent, ExtractionOutcome};
+pub use extractors::{
+    ExtractUserInfoAs, ExtractUserInfoWithAuthorization, ExtractUserInfoWithCacheHit,
+    ExtractUserInfoWithDuration, ExtractUserInfoWithLength, ExtractUserInfoWithUserId,
+    ExtractUserInfoWithValidation, UserInfoAsResult,
+};
+pub use fingerprint::ExtractUserInfoWithFingerprint;
+pub use identity::OwnedIdentity;
+pub use layer::{RequireUserInfo, RequireUserInfoLayer, require_user_info_layer};
+pub use lazy::LazyUserInfo;
+pub use multi::ExtractUserInfos;
+pub use nonce::ExpectedNonce;
+pub use owner::ResourceOwner;
+pub use provider::{IdentityProvider, ProviderIdentity};
+pub use query::NestedClaims;
+pub use redact::DEFAULT_SENSITIVE_KEYS;
+pub use rejection::{ExtractUserInfoRejection, UserInfoRejection};
+pub use require::RequireConfig;
+pub use subject::{SubjectFormat, same_subject};
+pub use tenant::TenantUser;
+#[cfg(feature = "test-util")]
+pub use test_util::{InvalidKind, request_with_invalid_user_info, request_with_user_info};
+pub use transform::{KeyCase, Transform};
+pub use verified::VerifiedUserInfo;
 
 /// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header.
 /// If the `X-Endpoint-API-UserInfo` header is present and it's a valid base 64 encoded JSON value, it returns it.
@@ -39,66 +121,35 @@ pub struct ExtractUserInfo(pub Value);
 
 const HEADER_X_USER_INFO: &str = "X-Endpoint-API-UserInfo";
 
+/// An empty user info, i.e. an empty JSON object, e.g. for test scaffolding and default branches.
+impl Default for ExtractUserInfo {
+    fn default() -> Self {
+        ExtractUserInfo(Value::Object(Map::new()))
+    }
+}
+
 impl<S> FromRequestParts<S> for ExtractUserInfo
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ExtractUserInfoRejection;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let user_info = parts.headers.get(HEADER_X_USER_INFO);
+        let extraction = extract(parts).await?;
 
-        match user_info {
-            Some(user_info) => {
-                let user_info = user_info.to_str().unwrap().trim();
-                let decoded_user_info = general_purpose::STANDARD.decode(user_info);
-
-                if decoded_user_info.is_err() {
-                    error!(
-                        "[{}] Failed to decode base 64 due to : {:?}",
-                        HEADER_X_USER_INFO,
-                        decoded_user_info.err().unwrap()
-                    );
-
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Invalid {} : Not a valid base 64", HEADER_X_USER_INFO),
-                    ));
-                }
-
-                let parsed_user_info = serde_json::from_slice(&decoded_user_info.unwrap());
-
-                if parsed_user_info.is_err() {
-                    error!(
-                        "[{}] Failed to parse JSON due to : {:?}",
-                        HEADER_X_USER_INFO,
-                        parsed_user_info.err().unwrap()
-                    );
-
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Invalid {} : Not a valid JSON", HEADER_X_USER_INFO),
-                    ));
-                }
-
-                Ok(ExtractUserInfo(parsed_user_info.unwrap()))
-            }
-            None => Err((
-                StatusCode::BAD_REQUEST,
-                format!("Invalid {} : Not found", HEADER_X_USER_INFO),
-            )),
-        }
+        Ok(ExtractUserInfo(extraction.value))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{ExtractUserInfo, HEADER_X_USER_INFO};
+    use crate::{ExtractUserInfo, HEADER_X_USER_INFO, UserInfoConfig, test_logger};
     use axum::{
         body::Body,
         extract::FromRequestParts,
         http::{Request, StatusCode},
     };
+    use log::Level;
 
     #[tokio::test]
     async fn test_lib_extract_user_info_with_header_ok_one() {
@@ -125,7 +176,7 @@ mod tests {
                   "jti": "my-unique-id"
                 })
             ),
-            Err(err) => assert!(false, "Expected a valid user info : {:?}", err),
+            Err(err) => panic!("Expected a valid user info : {:?}", err),
         }
     }
 
@@ -157,7 +208,7 @@ mod tests {
                   "jti": "my-unique-id"
                 })
             ),
-            Err(err) => assert!(false, "Expected a valid user info : {:?}", err),
+            Err(err) => panic!("Expected a valid user info : {:?}", err),
         }
     }
 
@@ -173,7 +224,7 @@ mod tests {
         let user_info = ExtractUserInfo::from_request_parts(&mut parts.0, &()).await;
 
         match user_info {
-            Ok(_) => assert!(false, "Expected an error"),
+            Ok(_) => panic!("Expected an error"),
             Err(err) => assert_eq!(
                 err,
                 (
@@ -196,12 +247,15 @@ mod tests {
         let user_info = ExtractUserInfo::from_request_parts(&mut parts.0, &()).await;
 
         match user_info {
-            Ok(_) => assert!(false, "Expected an error"),
+            Ok(_) => panic!("Expected an error"),
             Err(err) => assert_eq!(
                 err,
                 (
                     StatusCode::BAD_REQUEST,
-                    format!("Invalid {} : Not a valid JSON", HEADER_X_USER_INFO)
+                    format!(
+                        "Invalid {} : Not a valid JSON at line 1 column 2",
+                        HEADER_X_USER_INFO
+                    )
                 )
             ),
         }
@@ -216,7 +270,7 @@ mod tests {
         let user_info = ExtractUserInfo::from_request_parts(&mut parts.0, &()).await;
 
         match user_info {
-            Ok(_) => assert!(false, "Expected an error"),
+            Ok(_) => panic!("Expected an error"),
             Err(err) => assert_eq!(
                 err,
                 (
@@ -226,4 +280,58 @@ mod tests {
             ),
         }
     }
+
+    #[tokio::test]
+    async fn test_lib_extract_user_info_trace_success_enabled() {
+        test_logger::init();
+
+        let request = Request::builder()
+            .header("X-Endpoint-API-UserInfo", "eyJpc3MiOiJteS1pc3N1ZXIiLCJzdWIiOiJteS1zdWJqZWN0IiwiYXVkIjoibXktYXVkaWVuY2UiLCJuYW1lIjoibXktbmFtZSIsImlhdCI6MTUxNjIzOTAyMiwiZXhwIjoxNTE2MjM5MDIyLCJuYmYiOjE1MTYyMzkwMjIsImp0aSI6Im15LXVuaXF1ZS1pZCJ9")
+            .extension(UserInfoConfig::new().trace_success(true))
+            .body(Body::empty())
+            .unwrap();
+
+        let mut parts = request.into_parts();
+
+        let user_info = ExtractUserInfo::from_request_parts(&mut parts.0, &()).await;
+
+        assert!(user_info.is_ok());
+        assert_eq!(
+            test_logger::records(),
+            vec![(
+                Level::Trace,
+                format!(
+                    "[{}] Extracted user info for subject Some(\"my-subject\") with 8 claim(s)",
+                    HEADER_X_USER_INFO
+                )
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lib_extract_user_info_trace_success_disabled() {
+        test_logger::init();
+
+        let request = Request::builder()
+            .header("X-Endpoint-API-UserInfo", "eyJpc3MiOiJteS1pc3N1ZXIiLCJzdWIiOiJteS1zdWJqZWN0IiwiYXVkIjoibXktYXVkaWVuY2UiLCJuYW1lIjoibXktbmFtZSIsImlhdCI6MTUxNjIzOTAyMiwiZXhwIjoxNTE2MjM5MDIyLCJuYmYiOjE1MTYyMzkwMjIsImp0aSI6Im15LXVuaXF1ZS1pZCJ9")
+            .body(Body::empty())
+            .unwrap();
+
+        let mut parts = request.into_parts();
+
+        let user_info = ExtractUserInfo::from_request_parts(&mut parts.0, &()).await;
+
+        assert!(user_info.is_ok());
+        assert!(test_logger::records().is_empty());
+    }
+
+    #[test]
+    fn test_lib_extract_user_info_default() {
+        let user_info = ExtractUserInfo::default();
+
+        assert_eq!(user_info.0, serde_json::json!({}));
+        assert_eq!(user_info.0.get("sub"), None);
+        assert!(user_info.groups().is_empty());
+        assert!(user_info.scopes().is_empty());
+    }
 }
@@ -0,0 +1,41 @@
+/// Log an error, unless the logging is disabled in the configuration.
+macro_rules! log_error {
+    ($config:expr, $($arg:tt)+) => {
+        if !$config.logging_disabled {
+            log::error!($($arg)+);
+        }
+    };
+}
+
+/// Log a rejection as an error, or as a warning in shadow mode since it isn't enforced, unless the logging is disabled in the configuration.
+macro_rules! log_rejection {
+    ($config:expr, $($arg:tt)+) => {
+        if !$config.logging_disabled {
+            if $config.shadow_mode {
+                log::warn!("{} (shadow mode, not enforced)", format_args!($($arg)+));
+            } else {
+                log::error!($($arg)+);
+            }
+        }
+    };
+}
+
+/// Log a warning, unless the logging is disabled in the configuration.
+macro_rules! log_warn {
+    ($config:expr, $($arg:tt)+) => {
+        if !$config.logging_disabled {
+            log::warn!($($arg)+);
+        }
+    };
+}
+
+/// Log a trace, unless the logging is disabled in the configuration.
+macro_rules! log_trace {
+    ($config:expr, $($arg:tt)+) => {
+        if !$config.logging_disabled {
+            log::trace!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use {log_error, log_rejection, log_trace, log_warn};
@@ -0,0 +1,243 @@
+use crate::{
+    ExtractUserInfoRejection, HEADER_X_USER_INFO, MultiIdentityHandling, UserInfoConfig,
+    UserInfoRejection, extract::parse, logging::log_error,
+};
+use axum::{
+    extract::FromRequestParts,
+    http::{HeaderMap, HeaderValue, request::Parts},
+};
+use serde_json::Value;
+
+/// This is a custom extractor for Axum that extracts the identities of a multi-identity `X-Endpoint-API-UserInfo` header, i.e. comma-separated base 64 encoded JSON values as concatenated by a batching proxy.
+/// It requires the `multi_identity` configuration, without which the header is decoded as a single identity.
+/// Each identity goes through the decoding, the trust boundary filter, before any validation, and the validation of `ExtractUserInfo`, along with the detached signature check.
+/// The other stages of the extraction don't apply, i.e. neither the content type hint, the time budget, the heavy decodes cap, the cache, the extraction events and the audit log, nor the bearer subject check, the nonce check and the user lookup, which apply to a single identity.
+/// When the invalid segments are skipped, the request is still rejected if none of them is valid, with the rejection of the first one.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ExtractUserInfos;
+///
+/// async fn handler(ExtractUserInfos(identities): ExtractUserInfos) {
+///     println!("Batch of {} identities", identities.len());
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtractUserInfos(pub Vec<Value>);
+
+impl<S> FromRequestParts<S> for ExtractUserInfos
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let config = parts
+            .extensions
+            .get::<UserInfoConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        parse_identities(&parts.headers, &config)
+            .map(ExtractUserInfos)
+            .map_err(|rejection| ExtractUserInfoRejection::new(rejection, &config))
+    }
+}
+
+/// Decode and parse each comma-separated identity of the `X-Endpoint-API-UserInfo` header value, according to the multi-identity handling.
+fn parse_identities(
+    headers: &HeaderMap,
+    config: &UserInfoConfig,
+) -> Result<Vec<Value>, UserInfoRejection> {
+    let user_info = headers.get(HEADER_X_USER_INFO);
+
+    let Some(handling) = config.multi_identity else {
+        return Ok(vec![parse(user_info, headers, config)?.value]);
+    };
+
+    let Some(user_info) = user_info else {
+        return Err(UserInfoRejection::NotFound);
+    };
+
+    let user_info = match user_info.to_str() {
+        Ok(user_info) => user_info,
+        Err(err) => {
+            log_error!(
+                config,
+                "[{}] Failed to read header due to : {:?}",
+                HEADER_X_USER_INFO,
+                err
+            );
+
+            return Err(UserInfoRejection::NotBase64);
+        }
+    };
+
+    let mut identities = Vec::new();
+    let mut first_rejection = None;
+
+    for (index, segment) in user_info.split(',').enumerate() {
+        let identity = HeaderValue::from_str(segment)
+            .map_err(|_| UserInfoRejection::NotBase64)
+            .and_then(|segment| parse(Some(&segment), headers, config));
+
+        match identity {
+            Ok(extraction) => identities.push(extraction.value),
+            Err(rejection) if handling == MultiIdentityHandling::SkipInvalid => {
+                log_error!(
+                    config,
+                    "[{}] Skipped the invalid identity #{} due to : {}",
+                    HEADER_X_USER_INFO,
+                    index,
+                    rejection.reason()
+                );
+
+                first_rejection.get_or_insert(rejection);
+            }
+            Err(rejection) => return Err(rejection),
+        }
+    }
+
+    match first_rejection {
+        Some(rejection) if identities.is_empty() => Err(rejection),
+        _ => Ok(identities),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExtractUserInfos, MultiIdentityHandling, UserInfoConfig, UserInfoRejection, fixtures,
+    };
+    use axum::{body::Body, extract::FromRequestParts, http::Request};
+    use serde_json::json;
+
+    fn parts(user_info: &str, config: UserInfoConfig) -> axum::http::request::Parts {
+        Request::builder()
+            .header("X-Endpoint-API-UserInfo", user_info)
+            .extension(config)
+            .body(Body::empty())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_two_identities() {
+        let other = json!({ "sub": "my-other-subject" });
+        let mut parts = parts(
+            &format!("{},{}", fixtures::USER_INFO, fixtures::encode(&other)),
+            UserInfoConfig::new().multi_identity(MultiIdentityHandling::Reject),
+        );
+
+        let ExtractUserInfos(identities) = ExtractUserInfos::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identities, vec![fixtures::user_info(), other]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_invalid_segment_rejected() {
+        let mut parts = parts(
+            &format!("{},this-is-not-a-base64", fixtures::USER_INFO),
+            UserInfoConfig::new().multi_identity(MultiIdentityHandling::Reject),
+        );
+
+        let identities = ExtractUserInfos::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(identities.unwrap_err(), UserInfoRejection::NotBase64);
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_invalid_segment_skipped() {
+        let mut parts = parts(
+            &format!("this-is-not-a-base64,{}", fixtures::USER_INFO),
+            UserInfoConfig::new().multi_identity(MultiIdentityHandling::SkipInvalid),
+        );
+
+        let ExtractUserInfos(identities) = ExtractUserInfos::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identities, vec![fixtures::user_info()]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_all_segments_skipped() {
+        let mut parts = parts(
+            "this-is-not-a-base64,dGhpcy1pcy1ub3QtYS1qc29u",
+            UserInfoConfig::new().multi_identity(MultiIdentityHandling::SkipInvalid),
+        );
+
+        let identities = ExtractUserInfos::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(identities.unwrap_err(), UserInfoRejection::NotBase64);
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_without_multi_identity() {
+        let mut parts = parts(fixtures::USER_INFO, UserInfoConfig::new());
+
+        let ExtractUserInfos(identities) = ExtractUserInfos::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identities, vec![fixtures::user_info()]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_hmac_signature_forged() {
+        let forged = fixtures::encode(&json!({ "sub": "admin", "role": "root" }));
+        let config = UserInfoConfig::new().hmac_signature("X-UserInfo-Signature", "my-key");
+
+        let mut single = parts(&forged, config.clone());
+        let identities = ExtractUserInfos::from_request_parts(&mut single, &()).await;
+
+        assert_eq!(
+            identities.unwrap_err(),
+            UserInfoRejection::SignatureMismatch
+        );
+
+        // Even misconfigured with `multi_identity`, no identity gets through.
+        let mut batch = parts(
+            &format!("{},{}", fixtures::USER_INFO, forged),
+            config.multi_identity(MultiIdentityHandling::SkipInvalid),
+        );
+        let identities = ExtractUserInfos::from_request_parts(&mut batch, &()).await;
+
+        assert_eq!(
+            identities.unwrap_err(),
+            UserInfoRejection::SignatureMismatch
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_extract_user_infos_trust_boundary_before_validation() {
+        let user_info = fixtures::encode(&json!({ "sub": "admin", "role": "root" }));
+        let config = UserInfoConfig::new()
+            .trust_boundary("X-Proxy-Secret", "my-secret", ["sub"])
+            .allowed_keys(["sub"]);
+
+        let mut single = parts(&user_info, config.clone());
+        let ExtractUserInfos(identities) = ExtractUserInfos::from_request_parts(&mut single, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identities, vec![json!({ "sub": "admin" })]);
+
+        let mut batch = parts(
+            &format!("{},{}", user_info, user_info),
+            config.multi_identity(MultiIdentityHandling::Reject),
+        );
+        let ExtractUserInfos(identities) = ExtractUserInfos::from_request_parts(&mut batch, &())
+            .await
+            .unwrap();
+
+        assert_eq!(identities, vec![json!({ "sub": "admin" }); 2]);
+    }
+}
@@ -0,0 +1,18 @@
+/// This is the nonce expected for the request, e.g. the one issued at the start of an OIDC flow, compared with the `nonce` claim when `UserInfoConfig::nonce_check` is enabled.
+/// As it differs per request, it's inserted in the request extensions, e.g. by a middleware reading it from the session, before the user info is extracted.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{extract::Request, middleware::Next, response::Response};
+/// use user_info_middleware::ExpectedNonce;
+///
+/// async fn expected_nonce(mut request: Request, next: Next) -> Response {
+///     // The nonce issued for this session, e.g. read from a cookie.
+///     request.extensions_mut().insert(ExpectedNonce("my-nonce".to_string()));
+///
+///     next.run(request).await
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedNonce(pub String);
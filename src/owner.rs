@@ -0,0 +1,75 @@
+use crate::{
+    ExtractUserInfoRejection, HEADER_X_USER_INFO, UserInfoConfig, UserInfoRejection,
+    extract::extract, logging::log_error,
+};
+use axum::{
+    extract::{FromRequestParts, RawPathParams},
+    http::request::Parts,
+};
+use serde_json::Value;
+
+const DEFAULT_OWNER_PATH_PARAM: &str = "id";
+
+/// This is a custom extractor for Axum that extracts the user info of the resource owner, i.e. whose `sub` claim equals the path parameter identifying the resource, e.g. `/users/{id}`.
+/// The path parameter is `id` by default, and can be configured via `UserInfoConfig::owner_path_param`.
+/// It rejects the request exactly as `ExtractUserInfo` does, and with a 403 Forbidden when the `sub` claim differs from the path parameter.
+/// A route without the path parameter is rejected too, failing closed.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::ResourceOwner;
+///
+/// async fn handler(ResourceOwner(user_info): ResourceOwner) {
+///     println!("Owner : {:?}", user_info);
+/// }
+///
+/// let app = Router::<()>::new().route("/users/{id}", get(handler));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResourceOwner(pub Value);
+
+impl<S> FromRequestParts<S> for ResourceOwner
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        let config = parts
+            .extensions
+            .get::<UserInfoConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        let name = config
+            .owner_path_param
+            .as_deref()
+            .unwrap_or(DEFAULT_OWNER_PATH_PARAM);
+
+        let params = RawPathParams::from_request_parts(parts, state).await.ok();
+        let owner = params
+            .as_ref()
+            .and_then(|params| params.iter().find(|(key, _)| *key == name))
+            .map(|(_, value)| value);
+
+        if owner.is_none() || owner != extraction.value.get("sub").and_then(Value::as_str) {
+            log_error!(
+                config,
+                "[{}] Rejected a subject not owning the resource identified by the path parameter {:?}",
+                HEADER_X_USER_INFO,
+                name
+            );
+
+            return Err(ExtractUserInfoRejection::new(
+                UserInfoRejection::NotResourceOwner,
+                &config,
+            ));
+        }
+
+        Ok(ResourceOwner(extraction.value))
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{
+    ExtractUserInfo, ExtractUserInfoRejection, OwnedIdentity, UserInfoConfig, UserInfoRejection,
+    extract::extract,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+
+/// This is the identity provider (IdP) whose claim shape matched the user info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdentityProvider {
+    /// Google, i.e. an `iss` claim being `https://accounts.google.com` or `accounts.google.com`.
+    Google,
+    /// Azure AD, i.e. an `iss` claim under `https://login.microsoftonline.com/` or `https://sts.windows.net/`, or both the `tid` and `oid` claims.
+    Azure,
+    /// Okta, i.e. an `iss` claim under an `okta.com` or `oktapreview.com` domain, or both the `uid` and `cid` claims.
+    Okta,
+    /// Any other identity provider, its claims being mapped as by `OwnedIdentity`.
+    Generic,
+}
+
+impl IdentityProvider {
+    /// Detect the identity provider from the shape of the claims, trying Google, Azure AD and Okta in turn.
+    fn detect(claims: &Value) -> Self {
+        let iss = claims
+            .get("iss")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let has = |claim: &str| claims.get(claim).is_some();
+        let host = iss
+            .strip_prefix("https://")
+            .and_then(|iss| iss.split('/').next())
+            .unwrap_or_default();
+
+        if matches!(iss, "https://accounts.google.com" | "accounts.google.com") {
+            IdentityProvider::Google
+        } else if iss.starts_with("https://login.microsoftonline.com/")
+            || iss.starts_with("https://sts.windows.net/")
+            || (has("tid") && has("oid"))
+        {
+            IdentityProvider::Azure
+        } else if host.ends_with(".okta.com")
+            || host.ends_with(".oktapreview.com")
+            || (has("uid") && has("cid"))
+        {
+            IdentityProvider::Okta
+        } else {
+            IdentityProvider::Generic
+        }
+    }
+}
+
+/// This is a custom extractor for Axum that extracts a normalized identity from the user info, whatever its identity provider, along with the matched one.
+/// The claims are mapped as by `OwnedIdentity`, then normalized according to the identity provider :
+///
+/// - Google : the `email` is only kept when `email_verified` is `true` ;
+/// - Azure AD : the `email` falls back on `preferred_username`, then on `upn`, and the app `roles` are merged into the `groups` ;
+/// - Okta : the `email` falls back on the `sub` claim, when it's an email.
+///
+/// It rejects the request exactly as `ExtractUserInfo` does, and with a 400 Bad Request when the `sub` claim is missing or isn't a string.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::{IdentityProvider, ProviderIdentity};
+///
+/// async fn handler(ProviderIdentity { provider, identity }: ProviderIdentity) {
+///     if provider == IdentityProvider::Azure {
+///         println!("Azure AD user : {}", identity.subject);
+///     }
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderIdentity {
+    /// The identity provider whose claim shape matched.
+    pub provider: IdentityProvider,
+    /// The normalized identity.
+    pub identity: OwnedIdentity,
+}
+
+impl ProviderIdentity {
+    /// Build the normalized identity from the claims, or `None` when the `sub` claim is missing or isn't a string.
+    fn from_claims(user_info: &ExtractUserInfo) -> Option<Self> {
+        let claims = &user_info.0;
+        let string = |claim: &str| claims.get(claim).and_then(Value::as_str).map(String::from);
+
+        let provider = IdentityProvider::detect(claims);
+        let mut identity = OwnedIdentity::from_claims(user_info)?;
+
+        match provider {
+            IdentityProvider::Google => {
+                if claims.get("email_verified") != Some(&Value::Bool(true)) {
+                    identity.email = None;
+                }
+            }
+            IdentityProvider::Azure => {
+                identity.email = identity
+                    .email
+                    .or_else(|| string("preferred_username"))
+                    .or_else(|| string("upn"));
+
+                let roles = match claims.get("roles") {
+                    Some(Value::Array(roles)) => roles.iter().filter_map(Value::as_str).collect(),
+                    Some(Value::String(role)) => vec![role.as_str()],
+                    _ => Vec::new(),
+                };
+
+                for role in roles {
+                    if !identity.groups.iter().any(|group| group == role) {
+                        identity.groups.push(role.to_string());
+                    }
+                }
+            }
+            IdentityProvider::Okta => {
+                if identity.email.is_none() && identity.subject.contains('@') {
+                    identity.email = Some(identity.subject.clone());
+                }
+            }
+            IdentityProvider::Generic => {}
+        }
+
+        Some(ProviderIdentity { provider, identity })
+    }
+}
+
+impl<S> FromRequestParts<S> for ProviderIdentity
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        ProviderIdentity::from_claims(&ExtractUserInfo(extraction.value)).ok_or_else(|| {
+            let config = parts
+                .extensions
+                .get::<UserInfoConfig>()
+                .cloned()
+                .unwrap_or_default();
+
+            ExtractUserInfoRejection::new(
+                UserInfoRejection::MissingClaim {
+                    claim: "sub".to_string(),
+                },
+                &config,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IdentityProvider, ProviderIdentity, fixtures};
+    use axum::extract::FromRequestParts;
+    use serde_json::{Value, json};
+
+    async fn provider_identity(user_info: &Value) -> ProviderIdentity {
+        let mut parts = fixtures::parts(Some(&fixtures::encode(user_info)), None);
+
+        ProviderIdentity::from_request_parts(&mut parts, &())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_provider_identity_google() {
+        let verified = provider_identity(&json!({
+            "iss": "https://accounts.google.com",
+            "sub": "110169484474386276334",
+            "email": "my-email@gmail.com",
+            "email_verified": true,
+            "name": "my-name"
+        }))
+        .await;
+        let unverified = provider_identity(&json!({
+            "iss": "accounts.google.com",
+            "sub": "110169484474386276334",
+            "email": "my-email@gmail.com",
+            "email_verified": false
+        }))
+        .await;
+
+        assert_eq!(verified.provider, IdentityProvider::Google);
+        assert_eq!(
+            verified.identity.email.as_deref(),
+            Some("my-email@gmail.com")
+        );
+        assert_eq!(verified.identity.name.as_deref(), Some("my-name"));
+        assert_eq!(unverified.provider, IdentityProvider::Google);
+        assert_eq!(unverified.identity.email, None);
+    }
+
+    #[tokio::test]
+    async fn test_provider_identity_azure() {
+        let identity = provider_identity(&json!({
+            "iss": "https://login.microsoftonline.com/9188040d-6c67-4c5b-b112-36a304b66dad/v2.0",
+            "sub": "AAAAAAAAAAAAAAAAAAAAAIkzqFVrSaSaFHy782bbtaQ",
+            "tid": "9188040d-6c67-4c5b-b112-36a304b66dad",
+            "oid": "00000000-0000-0000-66f3-3332eca7ea81",
+            "preferred_username": "my-email@nebeto.xyz",
+            "groups": ["admin"],
+            "roles": ["Reader", "admin"]
+        }))
+        .await;
+
+        assert_eq!(identity.provider, IdentityProvider::Azure);
+        assert_eq!(
+            identity.identity.email.as_deref(),
+            Some("my-email@nebeto.xyz")
+        );
+        assert_eq!(identity.identity.groups, vec!["admin", "Reader"]);
+    }
+
+    #[tokio::test]
+    async fn test_provider_identity_okta_and_generic() {
+        let okta = provider_identity(&json!({
+            "iss": "https://nebeto.okta.com/oauth2/default",
+            "sub": "my-email@nebeto.xyz",
+            "uid": "00uid4BxXw6I6TV4m0g3"
+        }))
+        .await;
+        let generic = provider_identity(&fixtures::user_info()).await;
+
+        assert_eq!(okta.provider, IdentityProvider::Okta);
+        assert_eq!(okta.identity.email.as_deref(), Some("my-email@nebeto.xyz"));
+        assert_eq!(generic.provider, IdentityProvider::Generic);
+        assert_eq!(generic.identity.subject, "my-subject");
+    }
+}
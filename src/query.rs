@@ -0,0 +1,159 @@
+use crate::ExtractUserInfo;
+use serde_json::Value;
+
+/// This is the handling of the nested claims, i.e. the objects and the arrays, when rendering the user info as a query string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NestedClaims {
+    /// Skip the nested claims.
+    #[default]
+    Skip,
+    /// Render the nested claims as JSON, e.g. `groups=%5B%22admin%22%5D`.
+    Json,
+}
+
+impl ExtractUserInfo {
+    /// Render the top-level scalar claims as a URL-encoded query string, i.e. `key=value&...`, for the legacy services expecting the identity as form params.
+    /// The nested claims and the `null` ones are skipped, see `to_query_string_with` to render the nested ones as JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "sub": "my-subject", "name": "My Name", "admin": true }));
+    ///
+    /// assert_eq!(user_info.to_query_string(), "admin=true&name=My+Name&sub=my-subject");
+    /// ```
+    pub fn to_query_string(&self) -> String {
+        self.to_query_string_with(NestedClaims::Skip)
+    }
+
+    /// Render the top-level claims as a URL-encoded query string, handling the nested claims as given.
+    /// The keys and values are encoded as `application/x-www-form-urlencoded`, a space being a `+`, and the `null` claims are skipped.
+    pub fn to_query_string_with(&self, nested: NestedClaims) -> String {
+        let Value::Object(claims) = &self.0 else {
+            return String::new();
+        };
+
+        claims
+            .iter()
+            .filter_map(|(key, claim)| {
+                let value = match claim {
+                    Value::Null => return None,
+                    Value::String(value) => value.clone(),
+                    Value::Bool(_) | Value::Number(_) => claim.to_string(),
+                    Value::Array(_) | Value::Object(_) => match nested {
+                        NestedClaims::Skip => return None,
+                        NestedClaims::Json => claim.to_string(),
+                    },
+                };
+
+                Some(format!("{}={}", form_encode(key), form_encode(&value)))
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// Encode the string as `application/x-www-form-urlencoded`, keeping the alphanumerics and `*-._`, a space being a `+`.
+fn form_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Get the value of the first query parameter with the given name, both being decoded as `application/x-www-form-urlencoded`, e.g. for a user info sent in a browser redirect.
+pub(crate) fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+        (form_decode(key) == name).then(|| form_decode(value))
+    })
+}
+
+/// Decode the `application/x-www-form-urlencoded` string, a `+` being a space and an invalid percent-encoded byte being kept as is.
+fn form_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let hex = |offset: usize| {
+            bytes
+                .get(index + offset)
+                .and_then(|byte| (*byte as char).to_digit(16))
+        };
+
+        match (bytes[index], hex(1), hex(2)) {
+            (b'+', _, _) => decoded.push(b' '),
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                index += 2;
+            }
+            (byte, _, _) => decoded.push(byte),
+        }
+
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractUserInfo, NestedClaims, fixtures, query::query_param};
+    use serde_json::json;
+
+    #[test]
+    fn test_query_to_query_string_representative() {
+        let user_info = ExtractUserInfo(fixtures::user_info());
+
+        assert_eq!(
+            user_info.to_query_string(),
+            "aud=my-audience&exp=1516239022&iat=1516239022&iss=my-issuer&jti=my-unique-id&name=my-name&nbf=1516239022&sub=my-subject"
+        );
+    }
+
+    #[test]
+    fn test_query_to_query_string_encoded_and_nested() {
+        let user_info = ExtractUserInfo(json!({
+            "email": "my.email+tag@nebeto.xyz",
+            "name": "François & co",
+            "groups": ["admin"],
+            "address": { "country": "FR" },
+            "locale": null
+        }));
+
+        assert_eq!(
+            user_info.to_query_string(),
+            "email=my.email%2Btag%40nebeto.xyz&name=Fran%C3%A7ois+%26+co"
+        );
+        assert_eq!(
+            user_info.to_query_string_with(NestedClaims::Json),
+            "address=%7B%22country%22%3A%22FR%22%7D&email=my.email%2Btag%40nebeto.xyz&groups=%5B%22admin%22%5D&name=Fran%C3%A7ois+%26+co"
+        );
+    }
+
+    #[test]
+    fn test_query_query_param_decoded() {
+        let query = "state=xyz&user%5Finfo=eyJzdWIiOiJteS1zdWJqZWN0In0%3D&name=My+Name&broken=%zz&user_info=ignored";
+
+        assert_eq!(
+            query_param(query, "user_info").as_deref(),
+            Some("eyJzdWIiOiJteS1zdWJqZWN0In0=")
+        );
+        assert_eq!(query_param(query, "name").as_deref(), Some("My Name"));
+        assert_eq!(query_param(query, "broken").as_deref(), Some("%zz"));
+        assert_eq!(query_param(query, "missing"), None);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::UserInfoConfig;
+use serde_json::Value;
+
+/// This is the default denylist of the sensitive claims, redacted wherever the user info is exposed for debugging.
+pub const DEFAULT_SENSITIVE_KEYS: [&str; 5] = [
+    "email",
+    "phone_number",
+    "address",
+    "birthdate",
+    "access_token",
+];
+
+/// This is the placeholder of a redacted claim value.
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// Redact the values of the sensitive claims, at any depth.
+pub(crate) fn redact(value: &Value, config: &UserInfoConfig) -> Value {
+    match value {
+        Value::Object(claims) => Value::Object(
+            claims
+                .iter()
+                .map(|(key, value)| {
+                    if config.is_sensitive(key) {
+                        (key.clone(), Value::String(REDACTED.to_string()))
+                    } else {
+                        (key.clone(), redact(value, config))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.iter().map(|value| redact(value, config)).collect())
+        }
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UserInfoConfig, redact::redact};
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_default() {
+        let value = json!({ "sub": "my-subject", "email": "me@nebeto.xyz", "name": "my-name" });
+
+        assert_eq!(
+            redact(&value, &UserInfoConfig::new()),
+            json!({ "sub": "my-subject", "email": "[REDACTED]", "name": "my-name" })
+        );
+    }
+
+    #[test]
+    fn test_redact_sensitive_keys() {
+        let config = UserInfoConfig::new().sensitive_keys(["email", "name"]);
+        let value = json!({ "sub": "my-subject", "email": "me@nebeto.xyz", "others": [{ "email": "you@nebeto.xyz" }] });
+
+        assert_eq!(
+            redact(&value, &config),
+            json!({ "sub": "my-subject", "email": "[REDACTED]", "others": [{ "email": "[REDACTED]" }] })
+        );
+    }
+}
@@ -0,0 +1,699 @@
+use crate::{
+    HEADER_X_USER_INFO, HeaderEncoding, JsonType, UserInfoConfig, consumer::HEADER_X_CONSUMER,
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::fmt;
+
+/// This is the rejection returned by the extractors of this crate when the user info can't be extracted.
+/// It's rendered as a response with its status code and a specific message, e.g. `Invalid X-Endpoint-API-UserInfo : Not found`.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use user_info_middleware::UserInfoRejection;
+///
+/// let rejection = UserInfoRejection::NotFound;
+///
+/// assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+/// assert_eq!(rejection.to_string(), "Invalid X-Endpoint-API-UserInfo : Not found");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UserInfoRejection {
+    /// The `X-Endpoint-API-UserInfo` header is missing.
+    NotFound,
+    /// The `X-Endpoint-API-UserInfo` header isn't a valid base 64.
+    NotBase64,
+    /// The decoded `X-Endpoint-API-UserInfo` header isn't a valid JSON, with the position of the first error.
+    NotJson { line: usize, column: usize },
+    /// The decoded `X-Endpoint-API-UserInfo` header isn't a JSON object, while an object is required.
+    NotObject,
+    /// The decoded `X-Endpoint-API-UserInfo` header is an empty JSON array, while it's rejected.
+    EmptyArray,
+    /// The decoded `X-Endpoint-API-UserInfo` header has an array, at the given JSON Pointer path, with more elements than allowed.
+    ArrayTooLong { path: String, max: usize },
+    /// The decoded `X-Endpoint-API-UserInfo` header has a string, at the given JSON Pointer path, with an ASCII control character.
+    ControlCharacter { path: String },
+    /// The `aud` claim has none of the expected audiences.
+    InvalidAudience,
+    /// The user lookup didn't find the user, rejected with the configured status code.
+    UnknownUser(StatusCode),
+    /// The `sub` claim differs from the one of the `Authorization` bearer.
+    SubjectMismatch,
+    /// The `sub` claim is missing or doesn't match the required format.
+    InvalidSubject,
+    /// The configured JSON Pointer, selecting the claims, doesn't resolve in the decoded `X-Endpoint-API-UserInfo` header.
+    PointerNotFound { pointer: String },
+    /// The extraction exceeded the configured time budget.
+    Timeout,
+    /// The decoded `X-Endpoint-API-UserInfo` header doesn't deserialize into the expected type.
+    InvalidClaims,
+    /// The `iss` claim is one of the denylisted issuers.
+    DeniedIssuer,
+    /// The `X-Endpoint-API-Consumer` header isn't a valid base 64 encoded JSON.
+    InvalidConsumer,
+    /// The `X-Endpoint-API-UserInfo` header has a length, padding included, not being a multiple of 4.
+    InvalidBase64Length { len: usize },
+    /// The claim, required by the extractor, is missing or isn't a string.
+    MissingClaim { claim: String },
+    /// The `exp` claim is expired.
+    Expired,
+    /// The `iat` claim is older than the configured max age.
+    Stale,
+    /// The concurrent heavy decodes cap is reached, while the overflowing ones are rejected.
+    Overloaded,
+    /// The `UserInfoConfig` extension, required by `RequireConfig`, is missing, i.e. the server is misconfigured.
+    MissingConfig,
+    /// The header value isn't valid in the configured encoding, other than base 64.
+    InvalidEncoding { encoding: HeaderEncoding },
+    /// The `sub` claim differs from the path parameter identifying the resource owner, or the path parameter is missing.
+    NotResourceOwner,
+    /// The decoded `X-Endpoint-API-UserInfo` header isn't a valid CBOR value, or has no JSON counterpart.
+    NotCbor,
+    /// The decoded `X-Endpoint-API-UserInfo` header is a JSON `null`, while an object is required.
+    Null,
+    /// The header value is longer than the configured max length.
+    TooLarge { len: usize, max: usize },
+    /// The decoded `X-Endpoint-API-UserInfo` header has a top-level claim not being one of the allowed keys.
+    UnexpectedKey { key: String },
+    /// The `nonce` claim doesn't match the `ExpectedNonce` of the request.
+    NonceMismatch,
+    /// The decoded header value is longer than the configured max decoded length.
+    PayloadTooLarge { max: usize },
+    /// The decoded `X-Endpoint-API-UserInfo` header exceeds the parse guard, at the given byte offset.
+    ParseAborted { offset: usize },
+    /// The companion content type header hints another media type than `application/json`.
+    UnsupportedContentType { content_type: String },
+    /// The `aud` claim is a JSON object, while it's rejected.
+    AudienceObject,
+    /// The array claim has duplicate elements, while its elements must be unique.
+    DuplicateValue { claim: String },
+    /// The detached signature of the user info, i.e. the HMAC of its decoded bytes, is missing or mismatching.
+    SignatureMismatch,
+    /// The `nbf` or the `iat` claim is in the future, i.e. the user info isn't yet valid.
+    NotYetValid,
+    /// The decoded `X-Endpoint-API-UserInfo` header is a JSON value of a top-level type not being accepted.
+    UnacceptedType { kind: JsonType },
+    /// The body buffered to read the trailers of the request is longer than the configured limit, see `UserInfoConfig::trailer_body_limit`.
+    BodyTooLarge { max: usize },
+}
+
+const GRPC_UNKNOWN: (i32, &str) = (2, "UNKNOWN");
+const GRPC_INVALID_ARGUMENT: (i32, &str) = (3, "INVALID_ARGUMENT");
+const GRPC_DEADLINE_EXCEEDED: (i32, &str) = (4, "DEADLINE_EXCEEDED");
+const GRPC_NOT_FOUND: (i32, &str) = (5, "NOT_FOUND");
+const GRPC_PERMISSION_DENIED: (i32, &str) = (7, "PERMISSION_DENIED");
+const GRPC_RESOURCE_EXHAUSTED: (i32, &str) = (8, "RESOURCE_EXHAUSTED");
+const GRPC_INTERNAL: (i32, &str) = (13, "INTERNAL");
+const GRPC_UNAVAILABLE: (i32, &str) = (14, "UNAVAILABLE");
+const GRPC_UNAUTHENTICATED: (i32, &str) = (16, "UNAUTHENTICATED");
+
+impl UserInfoRejection {
+    /// Get the status code of the response rendered for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            UserInfoRejection::InvalidAudience
+            | UserInfoRejection::SubjectMismatch
+            | UserInfoRejection::SignatureMismatch
+            | UserInfoRejection::Expired
+            | UserInfoRejection::Stale
+            | UserInfoRejection::NotYetValid => StatusCode::UNAUTHORIZED,
+            UserInfoRejection::UnknownUser(status) => *status,
+            UserInfoRejection::Timeout => StatusCode::REQUEST_TIMEOUT,
+            UserInfoRejection::DeniedIssuer => StatusCode::FORBIDDEN,
+            UserInfoRejection::Overloaded => StatusCode::SERVICE_UNAVAILABLE,
+            UserInfoRejection::MissingConfig => StatusCode::INTERNAL_SERVER_ERROR,
+            UserInfoRejection::NotResourceOwner => StatusCode::FORBIDDEN,
+            UserInfoRejection::TooLarge { .. } => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            UserInfoRejection::PayloadTooLarge { .. } | UserInfoRejection::BodyTooLarge { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            UserInfoRejection::UnsupportedContentType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// Check whether this rejection is about the shape of the claims, i.e. a valid base 64 encoded and valid JSON user info not matching the expected schema, e.g. a missing claim, rather than a transport error.
+    /// Its status can be configured apart via `shape_error_status`, e.g. as a 422 Unprocessable Entity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::UserInfoRejection;
+    ///
+    /// assert!(UserInfoRejection::NotObject.is_shape_error());
+    /// assert!(!UserInfoRejection::NotBase64.is_shape_error());
+    /// ```
+    pub fn is_shape_error(&self) -> bool {
+        matches!(
+            self,
+            UserInfoRejection::NotObject
+                | UserInfoRejection::Null
+                | UserInfoRejection::EmptyArray
+                | UserInfoRejection::ArrayTooLong { .. }
+                | UserInfoRejection::ControlCharacter { .. }
+                | UserInfoRejection::InvalidSubject
+                | UserInfoRejection::PointerNotFound { .. }
+                | UserInfoRejection::InvalidClaims
+                | UserInfoRejection::MissingClaim { .. }
+                | UserInfoRejection::UnexpectedKey { .. }
+                | UserInfoRejection::AudienceObject
+                | UserInfoRejection::DuplicateValue { .. }
+                | UserInfoRejection::UnacceptedType { .. }
+        )
+    }
+
+    /// Get the gRPC status, as its numeric code and its name, matching this rejection, e.g. for services exposing both HTTP and gRPC.
+    /// A missing user info or an unauthenticated one is `UNAUTHENTICATED`, a malformed one is `INVALID_ARGUMENT`.
+    ///
+    /// # Links
+    ///
+    /// https://grpc.io/docs/guides/status-codes/
+    pub fn grpc_status(&self) -> (i32, &'static str) {
+        match self {
+            UserInfoRejection::NotFound
+            | UserInfoRejection::InvalidAudience
+            | UserInfoRejection::SubjectMismatch
+            | UserInfoRejection::SignatureMismatch
+            | UserInfoRejection::Expired
+            | UserInfoRejection::Stale
+            | UserInfoRejection::NotYetValid => GRPC_UNAUTHENTICATED,
+            UserInfoRejection::DeniedIssuer | UserInfoRejection::NotResourceOwner => {
+                GRPC_PERMISSION_DENIED
+            }
+            UserInfoRejection::Timeout => GRPC_DEADLINE_EXCEEDED,
+            UserInfoRejection::Overloaded => GRPC_UNAVAILABLE,
+            UserInfoRejection::MissingConfig => GRPC_INTERNAL,
+            UserInfoRejection::TooLarge { .. }
+            | UserInfoRejection::PayloadTooLarge { .. }
+            | UserInfoRejection::BodyTooLarge { .. } => GRPC_RESOURCE_EXHAUSTED,
+            UserInfoRejection::UnknownUser(status) => match *status {
+                StatusCode::BAD_REQUEST => GRPC_INVALID_ARGUMENT,
+                StatusCode::UNAUTHORIZED => GRPC_UNAUTHENTICATED,
+                StatusCode::FORBIDDEN => GRPC_PERMISSION_DENIED,
+                StatusCode::NOT_FOUND => GRPC_NOT_FOUND,
+                _ => GRPC_UNKNOWN,
+            },
+            _ => GRPC_INVALID_ARGUMENT,
+        }
+    }
+
+    /// Get the message of this rejection as rendered by the 0.2 release, or its own one when it has no counterpart.
+    fn legacy_message(&self) -> String {
+        match self {
+            UserInfoRejection::InvalidBase64Length { .. } => {
+                UserInfoRejection::NotBase64.to_string()
+            }
+            UserInfoRejection::NotJson { .. } => {
+                format!("Invalid {} : Not a valid JSON", self.header())
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Get the name of the header this rejection is about.
+    fn header(&self) -> &'static str {
+        match self {
+            UserInfoRejection::InvalidConsumer => HEADER_X_CONSUMER,
+            _ => HEADER_X_USER_INFO,
+        }
+    }
+
+    /// Get the generic name of the info this rejection is about, used instead of the header name when it's hidden.
+    fn label(&self) -> &'static str {
+        match self {
+            UserInfoRejection::InvalidConsumer => "consumer info",
+            _ => "user info",
+        }
+    }
+
+    /// Get the reason of this rejection, without the header name.
+    pub fn reason(&self) -> String {
+        match self {
+            UserInfoRejection::NotFound => "Not found".to_string(),
+            UserInfoRejection::NotBase64 => "Not a valid base 64".to_string(),
+            UserInfoRejection::NotJson { line, column } => {
+                format!("Not a valid JSON at line {} column {}", line, column)
+            }
+            UserInfoRejection::NotObject => "Not a JSON object".to_string(),
+            UserInfoRejection::EmptyArray => "Empty JSON array".to_string(),
+            UserInfoRejection::ArrayTooLong { path, max } => {
+                format!("Array at \"{}\" exceeds {} elements", path, max)
+            }
+            UserInfoRejection::ControlCharacter { path } => {
+                format!("Control character in string at \"{}\"", path)
+            }
+            UserInfoRejection::InvalidAudience => "Audience not accepted".to_string(),
+            UserInfoRejection::UnknownUser(_) => "Unknown user".to_string(),
+            UserInfoRejection::SubjectMismatch => "Subject mismatch with the bearer".to_string(),
+            UserInfoRejection::InvalidSubject => {
+                "Subject not matching the required format".to_string()
+            }
+            UserInfoRejection::PointerNotFound { pointer } => {
+                format!("Claims not found at \"{}\"", pointer)
+            }
+            UserInfoRejection::Timeout => "Extraction time budget exceeded".to_string(),
+            UserInfoRejection::InvalidClaims => "Claims not matching the expected type".to_string(),
+            UserInfoRejection::DeniedIssuer => "Issuer denied".to_string(),
+            UserInfoRejection::InvalidConsumer => "Not a valid base 64 encoded JSON".to_string(),
+            UserInfoRejection::InvalidBase64Length { len } => {
+                format!("Base 64 length {} not being a multiple of 4", len)
+            }
+            UserInfoRejection::MissingClaim { claim } => format!("Claim \"{}\" missing", claim),
+            UserInfoRejection::Expired => "Expired".to_string(),
+            UserInfoRejection::Stale => "Issued too long ago".to_string(),
+            UserInfoRejection::Overloaded => "Too many concurrent decodes".to_string(),
+            UserInfoRejection::MissingConfig => "Configuration missing".to_string(),
+            UserInfoRejection::InvalidEncoding { encoding } => {
+                format!("Not a valid {}", encoding.name())
+            }
+            UserInfoRejection::NotResourceOwner => "Subject not owning the resource".to_string(),
+            UserInfoRejection::NotCbor => "Not a valid CBOR".to_string(),
+            UserInfoRejection::Null => "JSON null instead of an object".to_string(),
+            UserInfoRejection::TooLarge { len, max } => {
+                format!("Length {} exceeding {} bytes", len, max)
+            }
+            UserInfoRejection::UnexpectedKey { key } => format!("Claim \"{}\" unexpected", key),
+            UserInfoRejection::NonceMismatch => "Nonce mismatch with the expected one".to_string(),
+            UserInfoRejection::PayloadTooLarge { max } => {
+                format!("Decoded length exceeding {} bytes", max)
+            }
+            UserInfoRejection::ParseAborted { offset } => {
+                format!("Parse aborted at byte {}", offset)
+            }
+            UserInfoRejection::UnsupportedContentType { content_type } => {
+                format!("Content type {:?} not supported", content_type)
+            }
+            UserInfoRejection::AudienceObject => {
+                "Audience being an object instead of a string or an array".to_string()
+            }
+            UserInfoRejection::DuplicateValue { claim } => {
+                format!("Claim \"{}\" having duplicate values", claim)
+            }
+            UserInfoRejection::SignatureMismatch => "Signature missing or mismatching".to_string(),
+            UserInfoRejection::NotYetValid => "Not yet valid".to_string(),
+            UserInfoRejection::UnacceptedType { kind } => format!("JSON {} not accepted", kind),
+            UserInfoRejection::BodyTooLarge { max } => format!("Body exceeding {} bytes", max),
+        }
+    }
+}
+
+impl fmt::Display for UserInfoRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {} : {}", self.header(), self.reason())
+    }
+}
+
+impl std::error::Error for UserInfoRejection {}
+
+impl IntoResponse for UserInfoRejection {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
+
+impl PartialEq<(StatusCode, String)> for UserInfoRejection {
+    fn eq(&self, other: &(StatusCode, String)) -> bool {
+        self.status() == other.0 && self.to_string() == other.1
+    }
+}
+
+/// This is the rejection returned by the extractors of this crate, i.e. a `UserInfoRejection` along with its rendered message.
+/// The message is the `UserInfoRejection` one, unless a custom message formatter is configured, e.g. for localization.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::{ExtractUserInfo, ExtractUserInfoRejection, UserInfoRejection};
+///
+/// async fn handler(user_info: Result<ExtractUserInfo, ExtractUserInfoRejection>) {
+///     match user_info {
+///         Ok(ExtractUserInfo(user_info)) => println!("User Info: {:?}", user_info),
+///         Err(err) if *err.rejection() == UserInfoRejection::NotFound => println!("Anonymous"),
+///         Err(err) => println!("Rejected: {}", err),
+///     }
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractUserInfoRejection {
+    rejection: UserInfoRejection,
+    status: StatusCode,
+    message: String,
+}
+
+impl ExtractUserInfoRejection {
+    /// Render the rejection with the configured message formatter and shape error status, if any.
+    pub(crate) fn new(rejection: UserInfoRejection, config: &UserInfoConfig) -> Self {
+        let status = match config.shape_error_status {
+            Some(status) if rejection.is_shape_error() => status,
+            _ => rejection.status(),
+        };
+        let message = match config.rejection_message {
+            Some(formatter) => formatter(&rejection),
+            None if config.legacy_messages => rejection.legacy_message(),
+            None if config.header_name_hidden => {
+                format!("Invalid {} : {}", rejection.label(), rejection.reason())
+            }
+            None => rejection.to_string(),
+        };
+
+        Self {
+            rejection,
+            status,
+            message,
+        }
+    }
+
+    /// Get the underlying rejection.
+    pub fn rejection(&self) -> &UserInfoRejection {
+        &self.rejection
+    }
+
+    /// Get the status code of the response rendered for this rejection, i.e. the configured shape error status, for a shape error, or the `UserInfoRejection` one.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Get the gRPC status matching this rejection.
+    pub fn grpc_status(&self) -> (i32, &'static str) {
+        self.rejection.grpc_status()
+    }
+
+    /// Get the message of the response rendered for this rejection.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<UserInfoRejection> for ExtractUserInfoRejection {
+    fn from(rejection: UserInfoRejection) -> Self {
+        let status = rejection.status();
+        let message = rejection.to_string();
+
+        Self {
+            rejection,
+            status,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ExtractUserInfoRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExtractUserInfoRejection {}
+
+impl IntoResponse for ExtractUserInfoRejection {
+    fn into_response(self) -> Response {
+        (self.status(), self.message).into_response()
+    }
+}
+
+impl PartialEq<UserInfoRejection> for ExtractUserInfoRejection {
+    fn eq(&self, other: &UserInfoRejection) -> bool {
+        self.rejection == *other
+    }
+}
+
+impl PartialEq<(StatusCode, String)> for ExtractUserInfoRejection {
+    fn eq(&self, other: &(StatusCode, String)) -> bool {
+        self.status() == other.0 && self.message == other.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExtractUserInfoRejection, HeaderEncoding, JsonType, UserInfoConfig, UserInfoRejection,
+    };
+    use axum::{http::StatusCode, response::IntoResponse};
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_rejection_into_response() {
+        let response = UserInfoRejection::NotJson {
+            line: 1,
+            column: 42,
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.into_body().collect().await.unwrap().to_bytes(),
+            "Invalid X-Endpoint-API-UserInfo : Not a valid JSON at line 1 column 42"
+        );
+    }
+
+    #[test]
+    fn test_rejection_extract_user_info_rejection_custom_message() {
+        let config = UserInfoConfig::new().rejection_message(|rejection| match rejection {
+            UserInfoRejection::NotFound => "Informations utilisateur introuvables".to_string(),
+            _ => "Informations utilisateur invalides".to_string(),
+        });
+
+        let rejection = ExtractUserInfoRejection::new(UserInfoRejection::NotFound, &config);
+
+        assert_eq!(
+            rejection,
+            (
+                StatusCode::BAD_REQUEST,
+                "Informations utilisateur introuvables".to_string()
+            )
+        );
+        assert_eq!(*rejection.rejection(), UserInfoRejection::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_rejection_extract_user_info_rejection_shape_error_status() {
+        let config = UserInfoConfig::new().shape_error_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        let shape = ExtractUserInfoRejection::new(UserInfoRejection::NotObject, &config);
+        let transport = ExtractUserInfoRejection::new(
+            UserInfoRejection::NotJson { line: 1, column: 7 },
+            &config,
+        );
+
+        assert_eq!(shape.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            shape.into_response().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(transport.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            ExtractUserInfoRejection::from(UserInfoRejection::NotObject).status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_rejection_extract_user_info_rejection_header_name_included() {
+        let config = UserInfoConfig::new().header_name_in_messages(true);
+
+        let rejection = ExtractUserInfoRejection::new(UserInfoRejection::NotBase64, &config);
+
+        assert_eq!(
+            rejection.message(),
+            "Invalid X-Endpoint-API-UserInfo : Not a valid base 64"
+        );
+    }
+
+    #[test]
+    fn test_rejection_extract_user_info_rejection_header_name_hidden() {
+        let config = UserInfoConfig::new().header_name_in_messages(false);
+
+        let rejection = ExtractUserInfoRejection::new(UserInfoRejection::NotBase64, &config);
+        let consumer_rejection =
+            ExtractUserInfoRejection::new(UserInfoRejection::InvalidConsumer, &config);
+
+        assert_eq!(
+            rejection,
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid user info : Not a valid base 64".to_string()
+            )
+        );
+        assert_eq!(
+            consumer_rejection.message(),
+            "Invalid consumer info : Not a valid base 64 encoded JSON"
+        );
+    }
+
+    #[test]
+    fn test_rejection_grpc_status() {
+        let rejections = [
+            (UserInfoRejection::NotFound, (16, "UNAUTHENTICATED")),
+            (UserInfoRejection::NotBase64, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::NotJson { line: 1, column: 1 },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::NotObject, (3, "INVALID_ARGUMENT")),
+            (UserInfoRejection::EmptyArray, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::ArrayTooLong {
+                    path: "/roles".to_string(),
+                    max: 1,
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (
+                UserInfoRejection::ControlCharacter {
+                    path: "/name".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::InvalidAudience, (16, "UNAUTHENTICATED")),
+            (
+                UserInfoRejection::UnknownUser(StatusCode::UNAUTHORIZED),
+                (16, "UNAUTHENTICATED"),
+            ),
+            (
+                UserInfoRejection::UnknownUser(StatusCode::FORBIDDEN),
+                (7, "PERMISSION_DENIED"),
+            ),
+            (
+                UserInfoRejection::UnknownUser(StatusCode::NOT_FOUND),
+                (5, "NOT_FOUND"),
+            ),
+            (
+                UserInfoRejection::UnknownUser(StatusCode::IM_A_TEAPOT),
+                (2, "UNKNOWN"),
+            ),
+            (UserInfoRejection::SubjectMismatch, (16, "UNAUTHENTICATED")),
+            (
+                UserInfoRejection::SignatureMismatch,
+                (16, "UNAUTHENTICATED"),
+            ),
+            (UserInfoRejection::InvalidSubject, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::PointerNotFound {
+                    pointer: "/claims".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::Timeout, (4, "DEADLINE_EXCEEDED")),
+            (UserInfoRejection::InvalidClaims, (3, "INVALID_ARGUMENT")),
+            (UserInfoRejection::DeniedIssuer, (7, "PERMISSION_DENIED")),
+            (UserInfoRejection::InvalidConsumer, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::InvalidBase64Length { len: 3 },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (
+                UserInfoRejection::MissingClaim {
+                    claim: "tenant_id".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::Expired, (16, "UNAUTHENTICATED")),
+            (UserInfoRejection::Stale, (16, "UNAUTHENTICATED")),
+            (UserInfoRejection::Overloaded, (14, "UNAVAILABLE")),
+            (UserInfoRejection::MissingConfig, (13, "INTERNAL")),
+            (
+                UserInfoRejection::InvalidEncoding {
+                    encoding: HeaderEncoding::Base64,
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (
+                UserInfoRejection::NotResourceOwner,
+                (7, "PERMISSION_DENIED"),
+            ),
+            (UserInfoRejection::NotCbor, (3, "INVALID_ARGUMENT")),
+            (UserInfoRejection::Null, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::TooLarge { len: 2, max: 1 },
+                (8, "RESOURCE_EXHAUSTED"),
+            ),
+            (
+                UserInfoRejection::UnexpectedKey {
+                    key: "admin".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::NonceMismatch, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::PayloadTooLarge { max: 1 },
+                (8, "RESOURCE_EXHAUSTED"),
+            ),
+            (
+                UserInfoRejection::ParseAborted { offset: 1 },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (
+                UserInfoRejection::UnsupportedContentType {
+                    content_type: "text/plain".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::AudienceObject, (3, "INVALID_ARGUMENT")),
+            (
+                UserInfoRejection::DuplicateValue {
+                    claim: "roles".to_string(),
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+            (UserInfoRejection::NotYetValid, (16, "UNAUTHENTICATED")),
+            (
+                UserInfoRejection::BodyTooLarge { max: 1 },
+                (8, "RESOURCE_EXHAUSTED"),
+            ),
+            (
+                UserInfoRejection::UnacceptedType {
+                    kind: JsonType::Array,
+                },
+                (3, "INVALID_ARGUMENT"),
+            ),
+        ];
+
+        for (rejection, grpc_status) in rejections {
+            assert_eq!(rejection.grpc_status(), grpc_status, "{:?}", rejection);
+        }
+    }
+
+    #[test]
+    fn test_rejection_extract_user_info_rejection_legacy_messages() {
+        let config = UserInfoConfig::new()
+            .legacy_messages(true)
+            .header_name_in_messages(false);
+
+        let rejection = ExtractUserInfoRejection::new(
+            UserInfoRejection::InvalidBase64Length { len: 3 },
+            &config,
+        );
+        let not_json = ExtractUserInfoRejection::new(
+            UserInfoRejection::NotJson {
+                line: 1,
+                column: 42,
+            },
+            &config,
+        );
+        let not_found = ExtractUserInfoRejection::new(UserInfoRejection::NotFound, &config);
+
+        assert_eq!(
+            rejection,
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid X-Endpoint-API-UserInfo : Not a valid base 64".to_string()
+            )
+        );
+        assert_eq!(
+            not_json.message(),
+            "Invalid X-Endpoint-API-UserInfo : Not a valid JSON"
+        );
+        assert_eq!(
+            not_found.message(),
+            "Invalid X-Endpoint-API-UserInfo : Not found"
+        );
+    }
+}
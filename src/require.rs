@@ -0,0 +1,89 @@
+use crate::{
+    ExtractUserInfoRejection, HEADER_X_USER_INFO, UserInfoConfig, UserInfoRejection,
+    logging::log_error,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+/// This is a custom extractor for Axum that wraps another extractor of this crate, failing closed when the `UserInfoConfig` extension is missing.
+/// The extractors otherwise fall back on the default configuration, silently skipping the configured validations, e.g. the expected audiences, when the `Extension` layer is forgotten on a route.
+/// If the `UserInfoConfig` extension is missing, it returns a 500 Internal Server Error, as it's a misconfiguration of the server rather than of the request.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Extension, Router};
+/// use user_info_middleware::{ExtractUserInfo, RequireConfig, UserInfoConfig};
+///
+/// async fn handler(RequireConfig(ExtractUserInfo(user_info)): RequireConfig<ExtractUserInfo>) {
+///     println!("User Info: {:?}", user_info);
+/// }
+///
+/// let app = Router::<()>::new()
+///     .route("/foo", get(handler))
+///     .layer(Extension(UserInfoConfig::new().require_object(true)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequireConfig<E>(pub E);
+
+impl<S, E> FromRequestParts<S> for RequireConfig<E>
+where
+    S: Send + Sync,
+    E: FromRequestParts<S, Rejection = ExtractUserInfoRejection>,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if parts.extensions.get::<UserInfoConfig>().is_none() {
+            let config = UserInfoConfig::default();
+
+            log_error!(
+                config,
+                "[{}] Rejected the request as the UserInfoConfig extension is missing",
+                HEADER_X_USER_INFO
+            );
+
+            return Err(ExtractUserInfoRejection::new(
+                UserInfoRejection::MissingConfig,
+                &config,
+            ));
+        }
+
+        E::from_request_parts(parts, state).await.map(RequireConfig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractUserInfo, RequireConfig, UserInfoConfig, UserInfoRejection, fixtures};
+    use axum::{extract::FromRequestParts, http::StatusCode};
+
+    #[tokio::test]
+    async fn test_require_config_present() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(UserInfoConfig::new()));
+
+        let RequireConfig(ExtractUserInfo(user_info)) =
+            RequireConfig::<ExtractUserInfo>::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(user_info, fixtures::user_info());
+    }
+
+    #[tokio::test]
+    async fn test_require_config_missing() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let rejection = RequireConfig::<ExtractUserInfo>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(rejection, UserInfoRejection::MissingConfig);
+        assert_eq!(
+            rejection,
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Invalid X-Endpoint-API-UserInfo : Configuration missing".to_string()
+            )
+        );
+    }
+}
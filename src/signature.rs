@@ -0,0 +1,39 @@
+use crate::sha256::{hmac_sha256, to_hex};
+use axum::http::HeaderMap;
+use std::fmt;
+
+/// This is a detached signature of the user info, i.e. the hexadecimal HMAC-SHA256 of its decoded bytes, sent by the trusted gateway in a companion header.
+#[derive(Clone)]
+pub(crate) struct DetachedSignature {
+    pub(crate) header: String,
+    pub(crate) key: Vec<u8>,
+}
+
+impl DetachedSignature {
+    /// Check whether the request holds the signature of the decoded bytes, exactly as sent, i.e. before any BOM stripping or parse.
+    /// The signature is compared case-insensitively and in constant time, for a given signature length, so it can't be guessed byte by byte.
+    pub(crate) fn is_valid(&self, headers: &HeaderMap, decoded: &[u8]) -> bool {
+        headers.get(&self.header).is_some_and(|value| {
+            let value = value.as_bytes().trim_ascii();
+            let expected = to_hex(&hmac_sha256(&self.key, decoded));
+
+            value.len() == expected.len()
+                && value
+                    .iter()
+                    .zip(expected.as_bytes())
+                    .fold(0, |difference, (a, b)| {
+                        difference | (a.to_ascii_lowercase() ^ b)
+                    })
+                    == 0
+        })
+    }
+}
+
+impl fmt::Debug for DetachedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DetachedSignature")
+            .field("header", &self.header)
+            .field("key", &"[REDACTED]")
+            .finish()
+    }
+}
@@ -0,0 +1,99 @@
+use serde_json::Value;
+
+/// This is the format required for the `sub` claim.
+#[derive(Debug, Clone, Copy)]
+pub enum SubjectFormat {
+    /// A UUID, in its hyphenated form, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`, whatever its case.
+    Uuid,
+    /// A custom format, checked by the given function.
+    Custom(fn(&str) -> bool),
+}
+
+impl SubjectFormat {
+    /// Check whether the subject matches the format.
+    pub fn matches(&self, subject: &str) -> bool {
+        match self {
+            SubjectFormat::Uuid => is_uuid(subject),
+            SubjectFormat::Custom(matches) => matches(subject),
+        }
+    }
+}
+
+/// Check whether two extracted user infos share the same authenticated subject, e.g. to rate-limit per user across a batch.
+/// Both `sub` claims must be strings, so a missing or a non string `sub` claim never matches, not even another missing one.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+/// use user_info_middleware::same_subject;
+///
+/// assert!(same_subject(&json!({ "sub": "my-subject", "name": "A" }), &json!({ "sub": "my-subject" })));
+/// assert!(!same_subject(&json!({ "name": "A" }), &json!({ "name": "A" })));
+/// ```
+pub fn same_subject(a: &Value, b: &Value) -> bool {
+    match (a.get("sub"), b.get("sub")) {
+        (Some(Value::String(a)), Some(Value::String(b))) => a == b,
+        _ => false,
+    }
+}
+
+fn is_uuid(subject: &str) -> bool {
+    let groups: Vec<&str> = subject.split('-').collect();
+
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len && group.chars().all(|character| character.is_ascii_hexdigit())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SubjectFormat, same_subject};
+    use serde_json::json;
+
+    #[test]
+    fn test_subject_format_uuid() {
+        assert!(SubjectFormat::Uuid.matches("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(SubjectFormat::Uuid.matches("67E55044-10B1-426F-9247-BB680E5FE0C8"));
+        assert!(!SubjectFormat::Uuid.matches("my-subject"));
+        assert!(!SubjectFormat::Uuid.matches("67e55044-10b1-426f-9247-bb680e5fe0c"));
+        assert!(!SubjectFormat::Uuid.matches("67e55044-10b1-426f-9247-bb680e5fe0cg"));
+    }
+
+    #[test]
+    fn test_subject_format_custom() {
+        let format = SubjectFormat::Custom(|subject| subject.starts_with("my-"));
+
+        assert!(format.matches("my-subject"));
+        assert!(!format.matches("subject"));
+    }
+
+    #[test]
+    fn test_subject_same_subject_equal() {
+        assert!(same_subject(
+            &json!({ "sub": "my-subject", "iat": 1516239022 }),
+            &json!({ "sub": "my-subject", "iat": 1516242622 })
+        ));
+    }
+
+    #[test]
+    fn test_subject_same_subject_unequal() {
+        assert!(!same_subject(
+            &json!({ "sub": "my-subject" }),
+            &json!({ "sub": "my-other-subject" })
+        ));
+        assert!(!same_subject(&json!({ "sub": 1 }), &json!({ "sub": 1 })));
+        assert!(!same_subject(&json!({ "sub": "1" }), &json!({ "sub": 1 })));
+    }
+
+    #[test]
+    fn test_subject_same_subject_missing() {
+        assert!(!same_subject(&json!({}), &json!({})));
+        assert!(!same_subject(&json!({ "sub": "my-subject" }), &json!(null)));
+        assert!(!same_subject(
+            &json!(["my-subject"]),
+            &json!({ "sub": "my-subject" })
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{ExtractUserInfoRejection, UserInfoConfig, UserInfoRejection, extract::extract};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+
+const DEFAULT_TENANT_CLAIM: &str = "tenant_id";
+const DEFAULT_USER_CLAIM: &str = "sub";
+
+/// This is a custom extractor for Axum that extracts the `(tenant, user)` pair from the user info, e.g. as the keys of a multi-tenant row-level security.
+/// The claims are `tenant_id` and `sub` by default, and can be configured via `UserInfoConfig::tenant_user_claims`.
+/// It rejects the request exactly as `ExtractUserInfo` does, and with a 400 Bad Request naming the claim when either claim is missing or isn't a string.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::TenantUser;
+///
+/// async fn handler(TenantUser { tenant, user }: TenantUser) {
+///     println!("Tenant : {}, User : {}", tenant, user);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantUser {
+    /// The tenant, from the `tenant_id` claim by default.
+    pub tenant: String,
+    /// The user, from the `sub` claim by default.
+    pub user: String,
+}
+
+impl<S> FromRequestParts<S> for TenantUser
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        let config = parts
+            .extensions
+            .get::<UserInfoConfig>()
+            .cloned()
+            .unwrap_or_default();
+
+        let (tenant_claim, user_claim) = match &config.tenant_user_claims {
+            Some((tenant_claim, user_claim)) => (tenant_claim.as_str(), user_claim.as_str()),
+            None => (DEFAULT_TENANT_CLAIM, DEFAULT_USER_CLAIM),
+        };
+
+        let claim = |claim: &str| match extraction.value.get(claim).and_then(Value::as_str) {
+            Some(value) => Ok(value.to_string()),
+            None => Err(ExtractUserInfoRejection::new(
+                UserInfoRejection::MissingClaim {
+                    claim: claim.to_string(),
+                },
+                &config,
+            )),
+        };
+
+        Ok(TenantUser {
+            tenant: claim(tenant_claim)?,
+            user: claim(user_claim)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TenantUser, UserInfoConfig, fixtures};
+    use axum::{extract::FromRequestParts, http::StatusCode};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tenant_tenant_user_both_present() {
+        let user_info = json!({ "tenant_id": "my-tenant", "sub": "my-subject" });
+        let mut parts = fixtures::parts(Some(&fixtures::encode(&user_info)), None);
+
+        let tenant_user = TenantUser::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            tenant_user.unwrap(),
+            TenantUser {
+                tenant: "my-tenant".to_string(),
+                user: "my-subject".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_tenant_user_configured_claims() {
+        let config = UserInfoConfig::new().tenant_user_claims("iss", "jti");
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), Some(config));
+
+        let tenant_user = TenantUser::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            tenant_user.unwrap(),
+            TenantUser {
+                tenant: "my-issuer".to_string(),
+                user: "my-unique-id".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_tenant_user_one_missing() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let tenant_user = TenantUser::from_request_parts(&mut parts, &()).await;
+
+        assert_eq!(
+            tenant_user.unwrap_err(),
+            (
+                StatusCode::BAD_REQUEST,
+                "Invalid X-Endpoint-API-UserInfo : Claim \"tenant_id\" missing".to_string()
+            )
+        );
+    }
+}
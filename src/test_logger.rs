@@ -0,0 +1,43 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{cell::RefCell, sync::Once};
+
+/// This is a test logger capturing the records emitted on the current thread.
+/// As `#[tokio::test]` runs on a current thread runtime, each test only sees its own records.
+struct TestLogger;
+
+thread_local! {
+    static RECORDS: RefCell<Vec<(Level, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+static INIT: Once = Once::new();
+
+impl Log for TestLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        RECORDS.with(|records| {
+            records
+                .borrow_mut()
+                .push((record.level(), record.args().to_string()))
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the test logger, once, and clear the records of the current thread.
+pub(crate) fn init() {
+    INIT.call_once(|| {
+        log::set_logger(&TestLogger).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+    });
+
+    RECORDS.with(|records| records.borrow_mut().clear());
+}
+
+/// Take the records emitted on the current thread since the last call.
+pub(crate) fn records() -> Vec<(Level, String)> {
+    RECORDS.with(|records| records.borrow_mut().drain(..).collect())
+}
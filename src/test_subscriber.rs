@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+use tracing::{
+    Event, Id, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Record},
+};
+use tracing_core::span::Current;
+
+/// This is a test `tracing` subscriber, handling a single span and capturing the values recorded in it.
+#[derive(Clone, Default)]
+pub(crate) struct TestSubscriber {
+    span: Arc<Mutex<Option<&'static Metadata<'static>>>>,
+    entered: Arc<Mutex<bool>>,
+    records: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl TestSubscriber {
+    /// Get the values recorded in the span so far.
+    pub(crate) fn records(&self) -> Vec<(String, String)> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+struct Visitor<'a>(&'a mut Vec<(String, String)>);
+
+impl Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+impl Subscriber for TestSubscriber {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        *self.span.lock().unwrap() = Some(span.metadata());
+
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, values: &Record<'_>) {
+        values.record(&mut Visitor(&mut self.records.lock().unwrap()));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {
+        *self.entered.lock().unwrap() = true;
+    }
+
+    fn exit(&self, _span: &Id) {
+        *self.entered.lock().unwrap() = false;
+    }
+
+    fn current_span(&self) -> Current {
+        match *self.span.lock().unwrap() {
+            Some(metadata) if *self.entered.lock().unwrap() => {
+                Current::new(Id::from_u64(1), metadata)
+            }
+            _ => Current::none(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::HEADER_X_USER_INFO;
+use axum::{body::Body, http::Request};
+use base64::{Engine, engine::general_purpose};
+use serde_json::Value;
+
+/// This is the kind of invalid `X-Endpoint-API-UserInfo` header built by `request_with_invalid_user_info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidKind {
+    /// A header value not being a valid base 64, rejected as `NotBase64`.
+    NotBase64,
+    /// A header value being a valid base 64 of an invalid JSON, rejected as `NotJson`.
+    NotJson,
+    /// An empty header value, rejected as `NotJson`, as it decodes into no bytes.
+    Empty,
+    /// No header at all, rejected as `NotFound`.
+    Missing,
+}
+
+/// Build a `GET /` test request holding the given claims, base 64 encoded, in the `X-Endpoint-API-UserInfo` header, e.g. to test a handler in a downstream crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use user_info_middleware::request_with_user_info;
+///
+/// let request = request_with_user_info(&serde_json::json!({ "sub": "my-subject" }));
+///
+/// assert!(request.headers().contains_key("X-Endpoint-API-UserInfo"));
+/// ```
+pub fn request_with_user_info(claims: &Value) -> Request<Body> {
+    request(Some(general_purpose::STANDARD.encode(claims.to_string())))
+}
+
+/// Build a `GET /` test request with an invalid or a missing `X-Endpoint-API-UserInfo` header, e.g. to test the rejection handling in a downstream crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use user_info_middleware::{InvalidKind, request_with_invalid_user_info};
+///
+/// let request = request_with_invalid_user_info(InvalidKind::Missing);
+///
+/// assert!(!request.headers().contains_key("X-Endpoint-API-UserInfo"));
+/// ```
+pub fn request_with_invalid_user_info(kind: InvalidKind) -> Request<Body> {
+    let user_info = match kind {
+        InvalidKind::NotBase64 => Some("not base 64 !".to_string()),
+        // The base 64 of `{"sub":`.
+        InvalidKind::NotJson => Some("eyJzdWIiOg==".to_string()),
+        InvalidKind::Empty => Some(String::new()),
+        InvalidKind::Missing => None,
+    };
+
+    request(user_info)
+}
+
+/// Build a `GET /` request with the given `X-Endpoint-API-UserInfo` header value, if any.
+fn request(user_info: Option<String>) -> Request<Body> {
+    let mut request = Request::builder().uri("/");
+
+    if let Some(user_info) = user_info {
+        request = request.header(HEADER_X_USER_INFO, user_info);
+    }
+
+    request.body(Body::empty()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExtractUserInfo, InvalidKind, UserInfoRejection, fixtures, request_with_invalid_user_info,
+        request_with_user_info,
+    };
+    use axum::extract::FromRequestParts;
+
+    #[tokio::test]
+    async fn test_test_util_request_with_user_info() {
+        let mut parts = request_with_user_info(&fixtures::user_info())
+            .into_parts()
+            .0;
+
+        let ExtractUserInfo(user_info) = ExtractUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info, fixtures::user_info());
+    }
+
+    #[tokio::test]
+    async fn test_test_util_request_with_invalid_user_info() {
+        let cases = [
+            (InvalidKind::NotBase64, UserInfoRejection::NotBase64),
+            (
+                InvalidKind::NotJson,
+                UserInfoRejection::NotJson { line: 1, column: 7 },
+            ),
+            (
+                InvalidKind::Empty,
+                UserInfoRejection::NotJson { line: 1, column: 0 },
+            ),
+            (InvalidKind::Missing, UserInfoRejection::NotFound),
+        ];
+
+        for (kind, expected) in cases {
+            let mut parts = request_with_invalid_user_info(kind).into_parts().0;
+
+            let rejection = ExtractUserInfo::from_request_parts(&mut parts, &())
+                .await
+                .unwrap_err();
+
+            assert_eq!(rejection, expected, "{:?}", kind);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use serde_json::{Map, Value};
+use std::{fmt, sync::Arc};
+
+/// This is a transform of the user info, registered via `UserInfoConfig::transform`.
+/// The transforms run sequentially, in their registration order, right after the parse and before any validation.
+///
+/// # Examples
+///
+/// ```rust
+/// use user_info_middleware::{Transform, UserInfoConfig};
+///
+/// let config = UserInfoConfig::new()
+///     .transform(Transform::StripControlCharacters)
+///     .transform(Transform::custom(|user_info| {
+///         if let Some(claims) = user_info.as_object_mut() {
+///             claims.remove("internal");
+///         }
+///     }));
+/// ```
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Transform {
+    /// Strip the ASCII control characters from all the string claims, at any depth.
+    StripControlCharacters,
+    /// Apply a JSON Merge Patch (RFC 7386) onto the user info.
+    MergePatch(Value),
+    /// Normalize the `aud` claim to always be an array, wrapping a string one into a single-element array.
+    /// An array or a missing `aud` claim is left untouched.
+    AudienceArray,
+    /// Remove the top-level string claims being empty once trimmed, e.g. `"email": ""`, so they're treated as absent.
+    RemoveEmptyStrings,
+    /// Normalize the object keys to the given case convention, e.g. `given_name` for `givenName` with `KeyCase::Snake`, either the top-level ones only or, when recursive, at any depth.
+    /// When two keys normalize to the same one, the first one, in key order, is kept.
+    /// As the recursive normalization rebuilds every object of the tree, allocating each of its keys, its cost grows with the whole payload rather than the top-level claims, so a large tree is better bounded, e.g. via `parse_guard`.
+    NormalizeKeys { case: KeyCase, recursive: bool },
+    /// Apply a custom transform.
+    Custom(Arc<dyn Fn(&mut Value) + Send + Sync>),
+}
+
+/// This is the case convention of the object keys normalized by `Transform::NormalizeKeys`.
+/// The words of a key are split on the `_`, `-`, `.` and space separators, and on the case changes, e.g. `XMLHttpRequest` being `xml`, `http` and `request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyCase {
+    /// The lowercase keys, e.g. `givenname`, the separators being kept.
+    Lower,
+    /// The snake case keys, e.g. `given_name`.
+    Snake,
+    /// The camel case keys, e.g. `givenName`.
+    Camel,
+}
+
+impl KeyCase {
+    /// Convert the key to the case convention.
+    fn convert(self, key: &str) -> String {
+        match self {
+            KeyCase::Lower => key.to_lowercase(),
+            KeyCase::Snake => words(key).join("_"),
+            KeyCase::Camel => words(key)
+                .into_iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    let mut characters = word.chars();
+
+                    match characters.next() {
+                        Some(first) if index > 0 => {
+                            first.to_uppercase().chain(characters).collect()
+                        }
+                        _ => word,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Transform {
+    /// Create a custom transform.
+    pub fn custom<F>(transform: F) -> Self
+    where
+        F: Fn(&mut Value) + Send + Sync + 'static,
+    {
+        Transform::Custom(Arc::new(transform))
+    }
+
+    /// Apply the transform onto the user info.
+    pub(crate) fn apply(&self, value: &mut Value) {
+        match self {
+            Transform::StripControlCharacters => strip_control_characters(value),
+            Transform::MergePatch(patch) => merge_patch(value, patch),
+            Transform::AudienceArray => audience_array(value),
+            Transform::RemoveEmptyStrings => remove_empty_strings(value),
+            Transform::NormalizeKeys { case, recursive } => {
+                normalize_keys(value, *case, *recursive)
+            }
+            Transform::Custom(transform) => transform(value),
+        }
+    }
+}
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::StripControlCharacters => f.write_str("StripControlCharacters"),
+            Transform::MergePatch(patch) => f.debug_tuple("MergePatch").field(patch).finish(),
+            Transform::AudienceArray => f.write_str("AudienceArray"),
+            Transform::RemoveEmptyStrings => f.write_str("RemoveEmptyStrings"),
+            Transform::NormalizeKeys { case, recursive } => f
+                .debug_struct("NormalizeKeys")
+                .field("case", case)
+                .field("recursive", recursive)
+                .finish(),
+            Transform::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Apply a JSON Merge Patch (RFC 7386) onto the value.
+pub(crate) fn merge_patch(value: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *value = patch.clone();
+        return;
+    };
+
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+
+    if let Value::Object(claims) = value {
+        for (key, patch) in patch {
+            if patch.is_null() {
+                claims.remove(key);
+            } else {
+                merge_patch(claims.entry(key).or_insert(Value::Null), patch);
+            }
+        }
+    }
+}
+
+/// Wrap a string `aud` claim into a single-element array.
+pub(crate) fn audience_array(value: &mut Value) {
+    if let Some(audience) = value.get_mut("aud")
+        && audience.is_string()
+    {
+        *audience = Value::Array(vec![audience.take()]);
+    }
+}
+
+/// Remove the top-level string claims being empty once trimmed.
+pub(crate) fn remove_empty_strings(value: &mut Value) {
+    if let Value::Object(claims) = value {
+        claims.retain(|_, claim| claim.as_str().is_none_or(|claim| !claim.trim().is_empty()));
+    }
+}
+
+/// Normalize the keys of the objects to the case convention, the top-level ones only or, when recursive, at any depth, including within the arrays.
+pub(crate) fn normalize_keys(value: &mut Value, case: KeyCase, recursive: bool) {
+    match value {
+        Value::Object(claims) => {
+            let mut normalized = Map::new();
+
+            for (key, mut claim) in std::mem::take(claims) {
+                if recursive {
+                    normalize_keys(&mut claim, case, recursive);
+                }
+
+                normalized.entry(case.convert(&key)).or_insert(claim);
+            }
+
+            *claims = normalized;
+        }
+        Value::Array(values) if recursive => values
+            .iter_mut()
+            .for_each(|value| normalize_keys(value, case, recursive)),
+        _ => {}
+    }
+}
+
+/// Split the key into its lowercase words, on the separators and on the case changes.
+fn words(key: &str) -> Vec<String> {
+    let characters: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (index, &character) in characters.iter().enumerate() {
+        if matches!(character, '_' | '-' | '.' | ' ') {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+
+            continue;
+        }
+
+        let previous = index.checked_sub(1).map(|index| characters[index]);
+        let next = characters.get(index + 1);
+        let boundary = character.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        word.extend(character.to_lowercase());
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Strip the ASCII control characters from all the strings, at any depth.
+pub(crate) fn strip_control_characters(value: &mut Value) {
+    match value {
+        Value::String(string) => string.retain(|character| !character.is_ascii_control()),
+        Value::Array(values) => values.iter_mut().for_each(strip_control_characters),
+        Value::Object(claims) => claims.values_mut().for_each(strip_control_characters),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        KeyCase, Transform,
+        transform::{
+            audience_array, normalize_keys, remove_empty_strings, strip_control_characters,
+        },
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_transform_strip_control_characters() {
+        let mut value = json!({ "name": "my-\nname\u{0}", "nested": ["my-\tvalue"], "iat": 1 });
+
+        strip_control_characters(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "name": "my-name", "nested": ["my-value"], "iat": 1 })
+        );
+    }
+
+    #[test]
+    fn test_transform_apply() {
+        let mut value = json!({ "name": "my-name" });
+
+        Transform::MergePatch(json!({ "name": null, "tenant": "my-tenant" })).apply(&mut value);
+
+        assert_eq!(value, json!({ "tenant": "my-tenant" }));
+    }
+
+    #[test]
+    fn test_transform_audience_array_string() {
+        let mut value = json!({ "aud": "my-audience" });
+
+        audience_array(&mut value);
+
+        assert_eq!(value, json!({ "aud": ["my-audience"] }));
+    }
+
+    #[test]
+    fn test_transform_audience_array_array() {
+        let mut value = json!({ "aud": ["my-audience", "my-other-audience"] });
+
+        Transform::AudienceArray.apply(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "aud": ["my-audience", "my-other-audience"] })
+        );
+    }
+
+    #[test]
+    fn test_transform_audience_array_missing() {
+        let mut value = json!({ "sub": "my-subject" });
+
+        Transform::AudienceArray.apply(&mut value);
+
+        assert_eq!(value, json!({ "sub": "my-subject" }));
+    }
+
+    #[test]
+    fn test_transform_remove_empty_strings() {
+        let mut value = json!({
+            "sub": "my-subject",
+            "email": "",
+            "name": "  ",
+            "groups": [""],
+            "nested": { "email": "" }
+        });
+
+        remove_empty_strings(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "sub": "my-subject", "groups": [""], "nested": { "email": "" } })
+        );
+    }
+
+    #[test]
+    fn test_transform_remove_empty_strings_kept() {
+        let mut value = json!({ "sub": " my-subject ", "iat": 0, "admin": false });
+
+        Transform::RemoveEmptyStrings.apply(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "sub": " my-subject ", "iat": 0, "admin": false })
+        );
+    }
+
+    #[test]
+    fn test_transform_normalize_keys_recursive() {
+        let mut value = json!({
+            "givenName": "my-name",
+            "custom-claims": {
+                "XMLHttpRequest": true,
+                "tenant.id": "my-tenant",
+                "roles": [{ "RoleName": "admin" }]
+            }
+        });
+
+        normalize_keys(&mut value, KeyCase::Snake, true);
+
+        assert_eq!(
+            value,
+            json!({
+                "given_name": "my-name",
+                "custom_claims": {
+                    "xml_http_request": true,
+                    "tenant_id": "my-tenant",
+                    "roles": [{ "role_name": "admin" }]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_transform_normalize_keys_top_level_and_camel() {
+        let mut value =
+            json!({ "given_name": "my-name", "address": { "street_address": "1 rue" } });
+
+        Transform::NormalizeKeys {
+            case: KeyCase::Camel,
+            recursive: false,
+        }
+        .apply(&mut value);
+
+        assert_eq!(
+            value,
+            json!({ "givenName": "my-name", "address": { "street_address": "1 rue" } })
+        );
+    }
+
+    #[test]
+    fn test_transform_normalize_keys_lower_and_collision() {
+        let mut value = json!({ "Email": "my-first-email", "email": "my-second-email", "Nested": { "Sub": 1 } });
+
+        normalize_keys(&mut value, KeyCase::Lower, true);
+
+        assert_eq!(
+            value,
+            json!({ "email": "my-first-email", "nested": { "sub": 1 } })
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+use std::{collections::BTreeSet, fmt};
+
+/// This is a trust boundary, telling the requests from the trusted proxy, holding its shared secret in a header, from the untrusted ones.
+/// The user info of an untrusted request only keeps its allowlisted top-level claims.
+#[derive(Clone)]
+pub(crate) struct TrustBoundary {
+    pub(crate) header: String,
+    pub(crate) secret: String,
+    pub(crate) allowed_claims: BTreeSet<String>,
+}
+
+impl TrustBoundary {
+    /// Check whether the request holds the shared secret of the trusted proxy.
+    /// The comparison runs in constant time, for a given secret length, so the secret can't be guessed byte by byte.
+    pub(crate) fn is_trusted(&self, headers: &HeaderMap) -> bool {
+        headers.get(&self.header).is_some_and(|value| {
+            let value = value.as_bytes();
+            let secret = self.secret.as_bytes();
+
+            value.len() == secret.len()
+                && value
+                    .iter()
+                    .zip(secret)
+                    .fold(0, |difference, (a, b)| difference | (a ^ b))
+                    == 0
+        })
+    }
+
+    /// Keep the allowlisted top-level claims only.
+    pub(crate) fn filter(&self, value: &mut Value) {
+        if let Value::Object(claims) = value {
+            claims.retain(|key, _| self.allowed_claims.contains(key));
+        }
+    }
+}
+
+impl fmt::Debug for TrustBoundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrustBoundary")
+            .field("header", &self.header)
+            .field("secret", &"[REDACTED]")
+            .field("allowed_claims", &self.allowed_claims)
+            .finish()
+    }
+}
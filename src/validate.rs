@@ -0,0 +1,228 @@
+use crate::ExpirationBoundary;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Check whether the `aud` claim, either a string or an array of strings, has at least one of the expected audiences.
+pub(crate) fn has_expected_audience(value: &Value, expected: &BTreeSet<String>) -> bool {
+    match value.get("aud") {
+        Some(Value::String(audience)) => expected.contains(audience),
+        Some(Value::Array(audiences)) => audiences
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|audience| expected.contains(audience)),
+        _ => false,
+    }
+}
+
+/// Check whether the array has duplicate elements, comparing each element with the previous ones.
+pub(crate) fn has_duplicate(values: &[Value]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .any(|(index, value)| values[..index].contains(value))
+}
+
+/// Find the path, as a JSON Pointer, of the first array having more than `max` elements, if any.
+pub(crate) fn find_array_too_long(value: &Value, max: usize) -> Option<String> {
+    find_array_too_long_at(value, max, String::new())
+}
+
+fn find_array_too_long_at(value: &Value, max: usize, path: String) -> Option<String> {
+    match value {
+        Value::Array(values) if values.len() > max => Some(path),
+        Value::Array(values) => values.iter().enumerate().find_map(|(index, value)| {
+            find_array_too_long_at(value, max, format!("{}/{}", path, index))
+        }),
+        Value::Object(claims) => claims.iter().find_map(|(key, value)| {
+            find_array_too_long_at(value, max, format!("{}/{}", path, escape(key)))
+        }),
+        _ => None,
+    }
+}
+
+/// Find the path, as a JSON Pointer, of the first string having an ASCII control character, if any.
+pub(crate) fn find_control_character(value: &Value) -> Option<String> {
+    find_control_character_at(value, String::new())
+}
+
+fn find_control_character_at(value: &Value, path: String) -> Option<String> {
+    match value {
+        Value::String(string) if string.chars().any(|character| character.is_ascii_control()) => {
+            Some(path)
+        }
+        Value::Array(values) => values.iter().enumerate().find_map(|(index, value)| {
+            find_control_character_at(value, format!("{}/{}", path, index))
+        }),
+        Value::Object(claims) => claims.iter().find_map(|(key, value)| {
+            find_control_character_at(value, format!("{}/{}", path, escape(key)))
+        }),
+        _ => None,
+    }
+}
+
+/// Check whether the `exp` claim, in seconds since the Unix epoch, is expired at the given time, according to the boundary.
+pub(crate) fn is_expired(value: &Value, now: u64, boundary: ExpirationBoundary) -> bool {
+    match value.get("exp").and_then(Value::as_u64) {
+        Some(exp) => match boundary {
+            ExpirationBoundary::Inclusive => now >= exp,
+            ExpirationBoundary::Exclusive => now > exp,
+        },
+        None => false,
+    }
+}
+
+/// Check whether the `iat` claim, in seconds since the Unix epoch, is older than the max age, in seconds, at the given time.
+pub(crate) fn is_stale(value: &Value, now: u64, max_age: u64) -> bool {
+    value
+        .get("iat")
+        .and_then(Value::as_u64)
+        .is_some_and(|iat| now.saturating_sub(iat) > max_age)
+}
+
+/// Check whether the `nbf` or the `iat` claim, in seconds since the Unix epoch, is in the future at the given time.
+pub(crate) fn is_premature(value: &Value, now: u64) -> bool {
+    ["nbf", "iat"].into_iter().any(|claim| {
+        value
+            .get(claim)
+            .and_then(Value::as_u64)
+            .is_some_and(|time| time > now)
+    })
+}
+
+/// Escape a key as a JSON Pointer reference token.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ExpirationBoundary,
+        validate::{
+            find_array_too_long, find_control_character, has_expected_audience, is_expired,
+            is_premature,
+        },
+    };
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_validate_find_array_too_long_at_cap() {
+        let value = json!({ "roles": ["a", "b"], "nested": { "groups": [["a", "b"]] } });
+
+        assert_eq!(find_array_too_long(&value, 2), None);
+    }
+
+    #[test]
+    fn test_validate_find_array_too_long_beyond_cap() {
+        let value = json!({ "roles": ["a"], "nested": { "a/b": [["a", "b", "c"]] } });
+
+        assert_eq!(
+            find_array_too_long(&value, 2),
+            Some("/nested/a~1b/0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_find_control_character() {
+        let value =
+            json!({ "name": "my-name", "nested": { "values": ["my-value", "my-\u{0}value"] } });
+
+        assert_eq!(
+            find_control_character(&value),
+            Some("/nested/values/1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_find_control_character_none() {
+        let value = json!({ "name": "my-name", "iat": 1 });
+
+        assert_eq!(find_control_character(&value), None);
+    }
+
+    #[test]
+    fn test_validate_has_expected_audience() {
+        let expected = BTreeSet::from(["my-audience".to_string()]);
+
+        assert!(has_expected_audience(
+            &json!({ "aud": "my-audience" }),
+            &expected
+        ));
+        assert!(has_expected_audience(
+            &json!({ "aud": ["my-other-audience", "my-audience"] }),
+            &expected
+        ));
+        assert!(!has_expected_audience(
+            &json!({ "aud": "my-other-audience" }),
+            &expected
+        ));
+        assert!(!has_expected_audience(
+            &json!({ "sub": "my-subject" }),
+            &expected
+        ));
+    }
+
+    #[test]
+    fn test_validate_is_expired_inclusive() {
+        let value = json!({ "iat": 1516239022, "exp": 1516239022 });
+
+        assert!(!is_expired(
+            &value,
+            1516239021,
+            ExpirationBoundary::Inclusive
+        ));
+        assert!(is_expired(
+            &value,
+            1516239022,
+            ExpirationBoundary::Inclusive
+        ));
+        assert!(is_expired(
+            &value,
+            1516239023,
+            ExpirationBoundary::Inclusive
+        ));
+    }
+
+    #[test]
+    fn test_validate_is_expired_exclusive() {
+        let value = json!({ "iat": 1516239022, "exp": 1516239022 });
+
+        assert!(!is_expired(
+            &value,
+            1516239021,
+            ExpirationBoundary::Exclusive
+        ));
+        assert!(!is_expired(
+            &value,
+            1516239022,
+            ExpirationBoundary::Exclusive
+        ));
+        assert!(is_expired(
+            &value,
+            1516239023,
+            ExpirationBoundary::Exclusive
+        ));
+    }
+
+    #[test]
+    fn test_validate_is_expired_missing() {
+        let value = json!({ "sub": "my-subject" });
+
+        assert!(!is_expired(
+            &value,
+            1516239022,
+            ExpirationBoundary::Inclusive
+        ));
+    }
+
+    #[test]
+    fn test_validate_is_premature() {
+        let value = json!({ "nbf": 1516239022, "iat": 1516239000 });
+
+        assert!(!is_premature(&value, 1516239022));
+        assert!(is_premature(&value, 1516239021));
+        assert!(is_premature(&json!({ "iat": 1516239022 }), 1516239021));
+        assert!(!is_premature(&json!({ "sub": "my-subject" }), 0));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    ExtractUserInfo, ExtractUserInfoRejection, UserInfoConfig,
+    extract::{enforced_rejection, extract},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+
+/// This is a custom extractor for Axum that extracts the user info, via the `X-Endpoint-API-UserInfo` header, as a guard type only constructible by the validating extraction.
+/// A function requiring a verified identity takes a `&VerifiedUserInfo`, so the type system prevents passing it raw or unvalidated claims, e.g. those of a deserialized payload.
+/// It rejects the request exactly as `ExtractUserInfo` does, so the claims went through all the configured validations, and it enforces them even in `shadow_mode`.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use user_info_middleware::VerifiedUserInfo;
+///
+/// fn audit(user_info: &VerifiedUserInfo) {
+///     println!("Auditing : {:?}", user_info.claims().get("sub"));
+/// }
+///
+/// async fn handler(user_info: VerifiedUserInfo) {
+///     audit(&user_info);
+/// }
+///
+/// let app = Router::<()>::new().route("/foo", get(handler));
+/// ```
+///
+/// It can't be built from raw claims :
+///
+/// ```rust,compile_fail
+/// use user_info_middleware::VerifiedUserInfo;
+///
+/// let user_info = VerifiedUserInfo(serde_json::json!({ "sub": "my-subject" }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedUserInfo(Value);
+
+impl VerifiedUserInfo {
+    /// Get the verified claims.
+    pub fn claims(&self) -> &Value {
+        &self.0
+    }
+
+    /// Get the verified claims as an `ExtractUserInfo`, e.g. for its accessors, dropping the guard.
+    pub fn into_user_info(self) -> ExtractUserInfo {
+        ExtractUserInfo(self.0)
+    }
+}
+
+impl<S> FromRequestParts<S> for VerifiedUserInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = ExtractUserInfoRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let extraction = extract(parts).await?;
+
+        if !extraction.validated {
+            let config = parts
+                .extensions
+                .get::<UserInfoConfig>()
+                .cloned()
+                .unwrap_or_default();
+
+            return Err(ExtractUserInfoRejection::new(
+                enforced_rejection(&extraction.value, &config),
+                &config,
+            ));
+        }
+
+        Ok(VerifiedUserInfo(extraction.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{UserInfoConfig, UserInfoRejection, VerifiedUserInfo, fixtures};
+    use axum::extract::FromRequestParts;
+
+    #[tokio::test]
+    async fn test_verified_verified_user_info_validated() {
+        let mut parts = fixtures::parts(Some(fixtures::USER_INFO), None);
+
+        let user_info = VerifiedUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(user_info.claims(), &fixtures::user_info());
+        assert_eq!(user_info.into_user_info().0, fixtures::user_info());
+    }
+
+    #[tokio::test]
+    async fn test_verified_verified_user_info_not_validated() {
+        let config = UserInfoConfig::new().require_jti(true);
+        let user_info = fixtures::encode(&serde_json::json!({ "sub": "my-subject" }));
+        let mut parts = fixtures::parts(Some(&user_info), Some(config));
+
+        let rejection = VerifiedUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            rejection,
+            UserInfoRejection::MissingClaim {
+                claim: "jti".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verified_verified_user_info_shadow_mode() {
+        let config = UserInfoConfig::new().require_jti(true).shadow_mode(true);
+        let user_info = fixtures::encode(&serde_json::json!({ "sub": "my-subject" }));
+        let mut parts = fixtures::parts(Some(&user_info), Some(config));
+
+        let rejection = VerifiedUserInfo::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            rejection,
+            UserInfoRejection::MissingClaim {
+                claim: "jti".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use axum::http::HeaderMap;
+use serde_json::{Map, Value};
+
+const HEADER_X_FORWARDED_CLIENT_CERT: &str = "X-Forwarded-Client-Cert";
+
+/// Synthesize the claims of the mTLS client certificate, from the `X-Forwarded-Client-Cert` (XFCC) header as forwarded by Envoy.
+/// Only the last element is read, i.e. the one appended by the nearest proxy, its `URI`, `DNS`, `Subject` and `Hash` keys being kept and the others ignored.
+/// The `sub` claim is the URI SAN, e.g. a SPIFFE ID, or else the CN of the subject, or else the first DNS SAN, the certificate being skipped without any of them.
+/// The certificate isn't verified, so the header must only be trusted from a proxy terminating the mTLS and sanitizing it.
+pub(crate) fn xfcc_claims(headers: &HeaderMap) -> Option<Value> {
+    let header = headers.get(HEADER_X_FORWARDED_CLIENT_CERT)?.to_str().ok()?;
+    let element = split_unquoted(header, ',').pop()?;
+
+    let mut uri = None;
+    let mut subject = None;
+    let mut hash = None;
+    let mut dns = Vec::new();
+
+    for pair in split_unquoted(element, ';') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let value = unquote(value.trim());
+
+        match key.trim().to_ascii_lowercase().as_str() {
+            "uri" => uri = uri.or(Some(value)),
+            "subject" => subject = Some(value),
+            "hash" => hash = Some(value),
+            "dns" => dns.push(Value::String(value)),
+            _ => {}
+        }
+    }
+
+    let common_name = subject.as_deref().and_then(|subject| {
+        subject
+            .split(',')
+            .find_map(|attribute| attribute.trim().strip_prefix("CN="))
+            .map(str::to_string)
+    });
+    let sub = uri
+        .clone()
+        .or(common_name)
+        .or_else(|| dns.first().and_then(Value::as_str).map(str::to_string))?;
+
+    let mut claims = Map::new();
+    claims.insert("sub".to_string(), Value::String(sub));
+
+    if let Some(uri) = uri {
+        claims.insert("cert_uri".to_string(), Value::String(uri));
+    }
+
+    if let Some(subject) = subject {
+        claims.insert("cert_subject".to_string(), Value::String(subject));
+    }
+
+    if !dns.is_empty() {
+        claims.insert("cert_dns".to_string(), Value::Array(dns));
+    }
+
+    if let Some(hash) = hash {
+        claims.insert("cert_hash".to_string(), Value::String(hash));
+    }
+
+    Some(Value::Object(claims))
+}
+
+/// Split the value on the separator, except within the double-quoted strings.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (index, character) in value.char_indices() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if character == separator && !quoted => {
+                parts.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Remove the double quotes around the value, if any, unescaping the escaped characters within.
+fn unquote(value: &str) -> String {
+    let Some(value) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+
+    let mut unquoted = String::with_capacity(value.len());
+    let mut characters = value.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => unquoted.extend(characters.next()),
+            _ => unquoted.push(character),
+        }
+    }
+
+    unquoted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xfcc::xfcc_claims;
+    use axum::http::{HeaderMap, HeaderValue};
+    use serde_json::json;
+
+    #[test]
+    fn test_xfcc_claims_representative() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-Client-Cert",
+            HeaderValue::from_static(
+                "By=spiffe://cluster.local/ns/foo/sa/edge;Hash=0123abcd;URI=spiffe://cluster.local/ns/bar/sa/gateway,By=spiffe://cluster.local/ns/foo/sa/api;Hash=4567cdef;Subject=\"CN=my-service,OU=my-team,O=Nebeto\";URI=spiffe://cluster.local/ns/foo/sa/my-service;DNS=my-service.foo.svc;DNS=my-service",
+            ),
+        );
+
+        assert_eq!(
+            xfcc_claims(&headers),
+            Some(json!({
+                "sub": "spiffe://cluster.local/ns/foo/sa/my-service",
+                "cert_uri": "spiffe://cluster.local/ns/foo/sa/my-service",
+                "cert_subject": "CN=my-service,OU=my-team,O=Nebeto",
+                "cert_dns": ["my-service.foo.svc", "my-service"],
+                "cert_hash": "4567cdef"
+            }))
+        );
+    }
+
+    #[test]
+    fn test_xfcc_claims_common_name() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Forwarded-Client-Cert",
+            HeaderValue::from_static("Hash=0123abcd;Subject=\"O=Nebeto, CN=my-\\\"service\\\"\""),
+        );
+
+        assert_eq!(
+            xfcc_claims(&headers).unwrap()["sub"],
+            json!("my-\"service\"")
+        );
+    }
+
+    #[test]
+    fn test_xfcc_claims_absent() {
+        let mut headers = HeaderMap::new();
+
+        assert_eq!(xfcc_claims(&headers), None);
+
+        headers.insert(
+            "X-Forwarded-Client-Cert",
+            HeaderValue::from_static("Hash=0123abcd"),
+        );
+
+        assert_eq!(xfcc_claims(&headers), None);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::ExtractUserInfo;
+use serde_json::Value;
+use serde_yaml::{Mapping, Value as YamlValue};
+
+impl ExtractUserInfo {
+    /// Serialize the user info as YAML, via `serde_yaml`, e.g. for human-facing debug endpoints.
+    /// Strings are emitted as plain scalars when unambiguous, otherwise quoted, e.g. `'true'` or a key starting with a digit.
+    /// With the `arbitrary_precision` feature, the numbers beyond 64 bits are emitted as their closest float.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use user_info_middleware::ExtractUserInfo;
+    ///
+    /// let user_info = ExtractUserInfo(serde_json::json!({ "sub": "my-subject", "roles": ["admin"] }));
+    ///
+    /// assert_eq!(user_info.to_yaml().unwrap(), "roles:\n- admin\nsub: my-subject\n");
+    /// ```
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&yaml_value(&self.0))
+    }
+}
+
+/// Convert the JSON value into its YAML counterpart, the numbers being converted by value, as their `arbitrary_precision` representation isn't understood by `serde_yaml`.
+fn yaml_value(value: &Value) -> YamlValue {
+    match value {
+        Value::Null => YamlValue::Null,
+        Value::Bool(value) => YamlValue::Bool(*value),
+        Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(number), _) => YamlValue::from(number),
+            (_, Some(number)) => YamlValue::from(number),
+            _ => number.as_f64().map_or(YamlValue::Null, YamlValue::from),
+        },
+        Value::String(value) => YamlValue::String(value.clone()),
+        Value::Array(values) => YamlValue::Sequence(values.iter().map(yaml_value).collect()),
+        Value::Object(claims) => YamlValue::Mapping(
+            claims
+                .iter()
+                .map(|(key, value)| (YamlValue::String(key.clone()), yaml_value(value)))
+                .collect::<Mapping>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ExtractUserInfo, fixtures};
+    use serde_json::json;
+
+    #[test]
+    fn test_yaml_to_yaml() {
+        let user_info = ExtractUserInfo(fixtures::user_info());
+
+        assert_eq!(
+            user_info.to_yaml().unwrap(),
+            "aud: my-audience\nexp: 1516239022\niat: 1516239022\niss: my-issuer\njti: my-unique-id\nname: my-name\nnbf: 1516239022\nsub: my-subject\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_to_yaml_nested() {
+        let user_info = ExtractUserInfo(json!({
+            "address": { "country": "FR", "lines": ["1 rue", "Paris"] },
+            "groups": [{ "id": 1, "name": "admin" }, []],
+            "empty": {},
+            "flag": "true"
+        }));
+
+        assert_eq!(
+            user_info.to_yaml().unwrap(),
+            "address:\n  country: FR\n  lines:\n  - 1 rue\n  - Paris\nempty: {}\nflag: 'true'\ngroups:\n- id: 1\n  name: admin\n- []\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_to_yaml_numbers() {
+        let user_info = ExtractUserInfo(json!({
+            "max": u64::MAX,
+            "min": i64::MIN,
+            "ratio": 0.5
+        }));
+
+        assert_eq!(
+            user_info.to_yaml().unwrap(),
+            "max: 18446744073709551615\nmin: -9223372036854775808\nratio: 0.5\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_to_yaml_quoted() {
+        let user_info = ExtractUserInfo(json!({
+            "1st": "2024",
+            "a: b": "x: y",
+            "#tag": "# not a comment",
+            "multi": "line 1\nline 2",
+            "name": "Zoë 😀",
+            "version": "1.0",
+            "empty": ""
+        }));
+
+        assert_eq!(
+            user_info.to_yaml().unwrap(),
+            "'#tag': '# not a comment'\n1st: '2024'\n'a: b': 'x: y'\nempty: ''\nmulti: |-\n  line 1\n  line 2\nname: Zoë 😀\nversion: '1.0'\n"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use axum::{
+    Extension, Router,
+    body::{Body, Bytes},
+    extract::Request as ExtractRequest,
+    http::{HeaderMap, HeaderValue, Request, StatusCode, header},
+    middleware,
+    response::Response,
+    routing::get,
+};
+use http_body_util::{BodyExt, Full};
+use hyper_util::rt::TokioIo;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tower::ServiceExt;
+use user_info_middleware::{
+    ExtractUserInfo, ExtractUserInfoWithUserId, ExtractionOutcome, ResourceOwner, UserInfoConfig,
+    UserInfoRejection, require_user_info_layer,
+};
+
+const USER_INFO: &str = "eyJpc3MiOiJteS1pc3N1ZXIiLCJzdWIiOiJteS1zdWJqZWN0IiwiYXVkIjoibXktYXVkaWVuY2UiLCJuYW1lIjoibXktbmFtZSIsImlhdCI6MTUxNjIzOTAyMiwiZXhwIjoxNTE2MjM5MDIyLCJuYmYiOjE1MTYyMzkwMjIsImp0aSI6Im15LXVuaXF1ZS1pZCJ9";
+
+#[tokio::test]
+async fn test_integration_extract_user_info_with_user_id() {
+    async fn handler(
+        user_info: ExtractUserInfoWithUserId,
+    ) -> (ExtractUserInfoWithUserId, &'static str) {
+        (user_info, "Hello")
+    }
+
+    let app = Router::new().route("/foo", get(handler));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .header("X-Endpoint-API-UserInfo", USER_INFO)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("X-User-Id").unwrap(), "my-subject");
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer() {
+    async fn protected(
+        Extension(ExtractUserInfo(user_info)): Extension<ExtractUserInfo>,
+    ) -> String {
+        user_info["sub"].as_str().unwrap().to_string()
+    }
+
+    async fn public() -> &'static str {
+        "OK"
+    }
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(UserInfoConfig::new()))
+        .merge(Router::new().route("/health", get(public)));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .header("X-Endpoint-API-UserInfo", USER_INFO)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "my-subject"
+    );
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/foo").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "Invalid X-Endpoint-API-UserInfo : Not found"
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_integration_extract_user_info_at_upgrade() {
+    async fn upgrade(
+        ExtractUserInfo(user_info): ExtractUserInfo,
+        mut request: ExtractRequest,
+    ) -> Response {
+        let on_upgrade = hyper::upgrade::on(&mut request);
+
+        tokio::spawn(async move {
+            let mut upgraded = TokioIo::new(on_upgrade.await.unwrap());
+
+            upgraded
+                .write_all(user_info["sub"].as_str().unwrap().as_bytes())
+                .await
+                .unwrap();
+            upgraded.shutdown().await.unwrap();
+        });
+
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = Router::new().route("/ws", get(upgrade));
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+
+    stream
+        .write_all(
+            format!(
+                "GET /ws HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nX-Endpoint-API-UserInfo: {}\r\n\r\n",
+                address, USER_INFO
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+    assert!(response.ends_with("\r\n\r\nmy-subject"));
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer_trailer_fallback() {
+    async fn protected(
+        Extension(ExtractUserInfo(user_info)): Extension<ExtractUserInfo>,
+        body: String,
+    ) -> String {
+        format!("{} {}", user_info["sub"].as_str().unwrap(), body)
+    }
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(
+            UserInfoConfig::new().trailer_fallback(true),
+        ));
+
+    let mut trailers = HeaderMap::new();
+    trailers.insert(
+        "X-Endpoint-API-UserInfo",
+        HeaderValue::from_static(USER_INFO),
+    );
+
+    let body =
+        Full::new(Bytes::from_static(b"hello")).with_trailers(async move { Some(Ok(trailers)) });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .body(Body::new(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "my-subject hello"
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .body(Body::from("hello"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer_trailer_body_limit() {
+    async fn protected() {}
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(
+            UserInfoConfig::new()
+                .trailer_fallback(true)
+                .trailer_body_limit(4),
+        ));
+
+    let mut trailers = HeaderMap::new();
+    trailers.insert(
+        "X-Endpoint-API-UserInfo",
+        HeaderValue::from_static(USER_INFO),
+    );
+
+    let body =
+        Full::new(Bytes::from_static(b"hello")).with_trailers(async move { Some(Ok(trailers)) });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .body(Body::new(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "Invalid X-Endpoint-API-UserInfo : Body exceeding 4 bytes"
+    );
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer_subject_hash_header() {
+    async fn protected(headers: HeaderMap) -> String {
+        headers["X-User-Hash"].to_str().unwrap().to_string()
+    }
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(
+            UserInfoConfig::new().subject_hash_header("X-User-Hash"),
+        ));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .header("X-Endpoint-API-UserInfo", USER_INFO)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // The SHA-256 of `my-subject`.
+    let expected = "f8667cd3dc63562e02523d52bfb8a41a85df4e30752d64511d15fca461c1ed02";
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["X-User-Hash"], expected);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        expected
+    );
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer_subject_hash_header_spoofed() {
+    async fn protected(headers: HeaderMap) -> String {
+        headers
+            .get("X-User-Hash")
+            .map(|hash| hash.to_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(
+            UserInfoConfig::new().subject_hash_header("X-User-Hash"),
+        ));
+
+    // `{"iss":"my-issuer"}`, i.e. without any `sub` to hash.
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/foo")
+                .header("X-Endpoint-API-UserInfo", "eyJpc3MiOiJteS1pc3N1ZXIifQ==")
+                .header("X-User-Hash", "spoofed")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.headers().contains_key("X-User-Hash"));
+    assert_eq!(response.into_body().collect().await.unwrap().to_bytes(), "");
+}
+
+#[tokio::test]
+async fn test_integration_require_user_info_layer_outcome_extension() {
+    async fn protected() {}
+
+    // An access log layer, running after the handler, reading the outcome.
+    async fn access_log(response: Response) -> Response {
+        assert!(response.extensions().get::<ExtractionOutcome>().is_some());
+
+        response
+    }
+
+    let app = Router::new()
+        .route("/foo", get(protected))
+        .route_layer(require_user_info_layer(
+            UserInfoConfig::new().outcome_extension(true),
+        ))
+        .layer(middleware::map_response(access_log));
+
+    let request = |user_info: &str| {
+        Request::builder()
+            .uri("/foo")
+            .header("X-Endpoint-API-UserInfo", user_info)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let extracted = app.clone().oneshot(request(USER_INFO)).await.unwrap();
+    let rejected = app.oneshot(request("not base 64 !")).await.unwrap();
+
+    assert_eq!(extracted.status(), StatusCode::OK);
+    assert_eq!(
+        extracted.extensions().get::<ExtractionOutcome>(),
+        Some(&ExtractionOutcome::Extracted)
+    );
+    assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        rejected.extensions().get::<ExtractionOutcome>(),
+        Some(&ExtractionOutcome::Rejected(UserInfoRejection::NotBase64))
+    );
+}
+
+#[tokio::test]
+async fn test_integration_resource_owner() {
+    async fn handler(ResourceOwner(user_info): ResourceOwner) -> String {
+        user_info["sub"].as_str().unwrap().to_string()
+    }
+
+    let app = Router::new()
+        .route("/users/{id}", get(handler))
+        .route("/me", get(handler));
+
+    let request = |uri: &str| {
+        Request::builder()
+            .uri(uri)
+            .header("X-Endpoint-API-UserInfo", USER_INFO)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let matching = app
+        .clone()
+        .oneshot(request("/users/my-subject"))
+        .await
+        .unwrap();
+
+    assert_eq!(matching.status(), StatusCode::OK);
+    assert_eq!(
+        matching.into_body().collect().await.unwrap().to_bytes(),
+        "my-subject"
+    );
+
+    let mismatching = app
+        .clone()
+        .oneshot(request("/users/my-other-subject"))
+        .await
+        .unwrap();
+
+    assert_eq!(mismatching.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        mismatching.into_body().collect().await.unwrap().to_bytes(),
+        "Invalid X-Endpoint-API-UserInfo : Subject not owning the resource"
+    );
+
+    let without_param = app.oneshot(request("/me")).await.unwrap();
+
+    assert_eq!(without_param.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_integration_resource_owner_configured_param() {
+    async fn handler(ResourceOwner(_): ResourceOwner) -> &'static str {
+        "OK"
+    }
+
+    let app = Router::new()
+        .route("/accounts/{account_id}", get(handler))
+        .layer(Extension(
+            UserInfoConfig::new().owner_path_param("account_id"),
+        ));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/accounts/my-subject")
+                .header("X-Endpoint-API-UserInfo", USER_INFO)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}